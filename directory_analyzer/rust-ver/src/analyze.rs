@@ -1,100 +1,387 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, DirEntry},
-    path::{self, PathBuf},
+    path::{self, Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
-use crate::{AnalyzedInfo, Args, FastPath, FileTypeInfo, FileTypeInfoRecords, SymlinkInfo, Timer};
+use crate::duplicates::find_duplicates;
+use crate::ignore::{has_ignore_options, GitignoreStack, IgnoreMatcher};
+use crate::{
+    file_extension, AgeHistogram, AgeTime, AnalyzedInfo, Args, BrokenSymlink, CategoryInfo,
+    CategoryMap, ContentType, DirSizes, DuplicateInfo, EntryError, ExtensionNormalizer,
+    FileExtension, FileTimeRecord, FileTypeInfo, FileTypeInfoRecords, LargestFiles, OwnerInfo,
+    PermissionAudit, SizeBasis, SizeHistogram, SnapshotTotals, SpecialFileInfo, SymlinkInfo, Timer,
+    TypeMismatch, WorkQueue, OTHER_CATEGORY,
+};
 use anyhow::Result;
+use file_id::FileId;
 
 pub fn analyze(args: &Args) -> Result<AnalyzedInfo> {
     let mut out = set_up_anaylzed_info(args);
     search_dirs(args, &mut out)?;
     out.calculate_percentages_for_info();
-    if let Some(sym) = out.found_symlinks() {
+    out.finish_size_histograms();
+    if let Some(categories) = out.categories_mut() {
+        categories.finish();
+    }
+    if let Some(owners) = out.owners_mut() {
+        owners.finish();
+    }
+    if let Some(audit) = out.permission_audit_mut() {
+        audit.finish();
+    }
+    if let Some(dir_sizes) = out.dir_sizes_mut() {
+        dir_sizes.roll_up();
+    }
+    if let Some(duplicates) = out.duplicates_mut() {
+        let errors = find_duplicates(duplicates, args.strict())?;
+        out.errors_mut().extend(errors);
+    }
+    if let Some(mount_points) = out.skipped_mount_points_mut() {
+        mount_points.sort();
+    }
+    if let Some(mismatches) = out.type_mismatches_mut() {
+        mismatches.sort_by(|a, b| a.path().cmp(b.path()));
+    }
+    out.errors_mut().sort_by(|a, b| a.path().cmp(b.path()));
+    if let Some(sym) = out.found_symlinks_mut() {
+        sym.sort();
         //Sanity check to make sure things add up
         debug_assert_eq!(
             sym.found_symlinks(),
-            sym.dir_symlinks() + sym.file_symlinks()
+            sym.dir_symlinks()
+                + sym.file_symlinks()
+                + sym.special_symlinks()
+                + sym.broken_symlinks()
         );
     }
     Ok(out)
 }
 
-fn search_dirs(args: &Args, analyed_info: &mut AnalyzedInfo) -> Result<()> {
-    let mut dirs_to_analyze = vec![args.path_to_analyze().clone()];
+///A directory waiting to be searched
+struct DirToAnalyze {
+    path: PathBuf,
+    ///Rules from ignore files that apply to `path` itself, only used with --respect-gitignore
+    gitignore: Option<Arc<GitignoreStack>>,
+}
+
+///When a file was modified, accessed or changed and how long ago that was, only used with --age
+#[derive(Debug, Clone, Copy)]
+struct FileAge {
+    ///Seconds since the Unix epoch
+    time: i64,
+    age_secs: u64,
+}
+
+///State shared between all the threads walking the directory tree
+struct SearchState {
+    dirs_to_analyze: WorkQueue<DirToAnalyze>,
     //used to prevent repeatedly counting the same item multiple times while following symlinks
-    let mut found_items = Vec::new();
-    let mut timer = args.updates().map(Timer::new);
-    let ignore_these = set_up_ignore_these(args)?;
-    while let Some(dir) = dirs_to_analyze.pop() {
-        let cur_dir = dir.read_dir()?;
+    //and files with several hard links,
+    //keyed by (device, inode) so hard links and bind mounts to the same object are also caught
+    found_items: Mutex<HashSet<FileId>>,
+    ignore: Option<IgnoreMatcher>,
+    ///Device of the path to analyze, only set with --one-file-system
+    root_device: Option<u64>,
+    ///When the analysis started in seconds since the Unix epoch, ages of files are relative to this
+    now: i64,
+    timer: Option<Mutex<Timer>>,
+    //Totals across all threads, only used for the periodic updates
+    found_dirs: AtomicU32,
+    found_files: AtomicU32,
+    extensions: ExtensionNormalizer,
+    categories: CategoryMap,
+}
+
+impl SearchState {
+    ///Returns true if the object at `path` has not been seen before and marks it as seen.
+    ///
+    ///Always true when not following symlinks since there is no risk of loops or double counting
+    fn first_visit(&self, args: &Args, path: &Path) -> Result<bool> {
+        if !args.follow_symlinks() {
+            return Ok(true);
+        }
+        let id = file_id::get_file_id(path)?;
+        Ok(self.found_items.lock().unwrap().insert(id))
+    }
+
+    ///Returns true if the file at `path` has not been counted before and marks it as counted.
+    ///
+    ///Unlike `first_visit` files that may have several hard links are checked even when not
+    ///following symlinks, so the bytes of each file are only counted once. Which of the links
+    ///is counted depends on the order they are found in
+    fn first_visit_file(&self, args: &Args, path: &Path, metadata: &fs::Metadata) -> Result<bool> {
+        if !args.follow_symlinks() && !may_be_hard_linked(metadata) {
+            return Ok(true);
+        }
+        let id = file_id::get_file_id(path)?;
+        Ok(self.found_items.lock().unwrap().insert(id))
+    }
+
+    ///If the directory at `path` is on a different filesystem than the path to analyze,
+    ///always false when not staying on one filesystem
+    fn crosses_mount_point(&self, path: &Path) -> Result<bool> {
+        match self.root_device {
+            Some(root_device) => Ok(device_id(path)? != root_device),
+            None => Ok(false),
+        }
+    }
+
+    ///The time used by --age of the file with `metadata` and how old it is, `None` without --age
+    fn file_age(&self, args: &Args, metadata: &fs::Metadata) -> Result<Option<FileAge>> {
+        if !args.age() {
+            return Ok(None);
+        }
+        let time = file_time(args.age_time(), metadata)?;
+        Ok(Some(FileAge {
+            time,
+            age_secs: self.now.saturating_sub(time).max(0) as u64,
+        }))
+    }
+
+    fn update_progress(&self, found_dirs: u32, found_files: u32) {
+        let Some(timer) = &self.timer else {
+            return;
+        };
+        let found_dirs = self.found_dirs.fetch_add(found_dirs, Ordering::Relaxed) + found_dirs;
+        let found_files = self.found_files.fetch_add(found_files, Ordering::Relaxed) + found_files;
+        //Another thread is already handling the update
+        let Ok(mut timer) = timer.try_lock() else {
+            return;
+        };
+        timer.update();
+        if timer.ended() {
+            println!("Update: {found_dirs} found dirs, {found_files} found files");
+            timer.reset();
+        }
+    }
+}
+
+///Walks the tree with `args.threads()` threads, each thread builds its own `AnalyzedInfo`
+///which are merged into `analyed_info` once all threads are finished
+fn search_dirs(args: &Args, analyed_info: &mut AnalyzedInfo) -> Result<()> {
+    let mut found_items = HashSet::new();
+    if args.follow_symlinks() {
+        //a symlink back to the root should not walk the whole tree a second time
+        found_items.insert(file_id::get_file_id(args.path_to_analyze())?);
+    }
+    let state = SearchState {
+        dirs_to_analyze: WorkQueue::new(vec![DirToAnalyze {
+            path: args.path_to_analyze().clone(),
+            gitignore: match args.respect_gitignore() {
+                true => GitignoreStack::for_root(args.path_to_analyze())?,
+                false => None,
+            },
+        }]),
+        found_items: Mutex::new(found_items),
+        ignore: IgnoreMatcher::new(args)?,
+        root_device: match args.one_file_system() {
+            true => Some(device_id(args.path_to_analyze())?),
+            false => None,
+        },
+        now: unix_secs(SystemTime::now()),
+        timer: args.updates().map(|u| Mutex::new(Timer::new(u))),
+        found_dirs: AtomicU32::new(0),
+        found_files: AtomicU32::new(0),
+        extensions: ExtensionNormalizer::new(args),
+        categories: CategoryMap::new(args)?,
+    };
+    let partials: Vec<_> = thread::scope(|s| {
+        let handles: Vec<_> = (0..args.threads())
+            .map(|_| {
+                s.spawn(|| {
+                    let res = search_dirs_worker(args, &state);
+                    if res.is_err() {
+                        state.dirs_to_analyze.abort();
+                    }
+                    res
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("directory walker thread panicked"))
+            .collect()
+    });
+    for partial in partials {
+        analyed_info.merge(partial?);
+    }
+    Ok(())
+}
+
+fn search_dirs_worker(args: &Args, state: &SearchState) -> Result<AnalyzedInfo> {
+    let mut analyed_info = set_up_anaylzed_info(args);
+    while let Some(dir) = state.dirs_to_analyze.pop() {
+        let (dirs_before, files_before) = (analyed_info.found_dirs(), analyed_info.found_files());
+        search_dir(args, state, &dir, &mut analyed_info)?;
+        state.update_progress(
+            analyed_info.found_dirs() - dirs_before,
+            analyed_info.found_files() - files_before,
+        );
+    }
+
+    fn search_dir(
+        args: &Args,
+        state: &SearchState,
+        dir: &DirToAnalyze,
+        analyed_info: &mut AnalyzedInfo,
+    ) -> Result<()> {
+        let gitignore = match args.respect_gitignore() {
+            true => GitignoreStack::for_dir(dir.gitignore.clone(), &dir.path),
+            false => None,
+        };
+        //Only needed to find files owned by a different user than their directory
+        let dir_uid = match analyed_info.permission_audit() {
+            Some(_) => dir.path.metadata().ok().and_then(|m| owner_ids(&m)),
+            None => None,
+        }
+        .map(|(uid, _)| uid);
+        let cur_dir = match dir.path.read_dir() {
+            Ok(cur_dir) => cur_dir,
+            //Nothing can be analyzed if the path to analyze itself can't be read
+            Err(e) if dir.path == *args.path_to_analyze() => return Err(e.into()),
+            Err(e) => return record_error(args, analyed_info, &dir.path, e.into()),
+        };
         for entry in cur_dir {
-            let entry = entry?;
-            let metadata = entry.metadata()?;
-            let path = path::absolute(entry.path())?;
-            if let Some(ignore_these) = &ignore_these {
-                if ignore_these
-                    .iter()
-                    .any(|p| path::absolute(p).unwrap() == path)
-                {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    record_error(args, analyed_info, &dir.path, e.into())?;
                     continue;
                 }
+            };
+            let path = entry.path();
+            if let Err(e) = search_entry(args, state, &gitignore, dir_uid, entry, analyed_info) {
+                record_error(args, analyed_info, &path, e)?;
             }
-            if metadata.is_dir() && !found_items.contains(&FastPath::new(&path)) {
-                handle_dirs(args, &mut dirs_to_analyze, &entry, analyed_info)?;
-                //No need to track found items since there is no risk of loops or double counting
-                if args.follow_symlinks() {
-                    found_items.push(FastPath::new(&path));
-                }
-            } else if metadata.is_file() && !found_items.contains(&FastPath::new(&path)) {
-                handle_files(args, analyed_info, &entry, &metadata)?;
-                //No need to track found items since there is no risk of loops or double counting
-                if args.follow_symlinks() {
-                    found_items.push(FastPath::new(&path));
-                }
-            } else if (args.count_symlinks() || args.follow_symlinks())
-                && metadata.is_symlink()
-                && !found_items.contains(&FastPath::new(&path))
-            {
-                handle_symlinks(
-                    entry,
-                    &mut found_items,
+        }
+        Ok(())
+    }
+
+    fn search_entry(
+        args: &Args,
+        state: &SearchState,
+        gitignore: &Option<Arc<GitignoreStack>>,
+        dir_uid: Option<u32>,
+        entry: DirEntry,
+        analyed_info: &mut AnalyzedInfo,
+    ) -> Result<()> {
+        let metadata = entry.metadata()?;
+        let path = entry.path();
+        if is_ignored(args, state, gitignore, &entry, &metadata) {
+            if let Some(ignored) = analyed_info.ignored_entries_mut() {
+                *ignored += 1;
+            }
+            return Ok(());
+        }
+        //Symlinks always have every permission so only what they point to is worth checking
+        if metadata.is_dir() || metadata.is_file() {
+            audit_permissions(args, analyed_info, &entry, &metadata, dir_uid)?;
+        }
+        if metadata.is_dir() {
+            if state.crosses_mount_point(&path)? {
+                handle_mount_point(args, analyed_info, &entry)?;
+            } else if state.first_visit(args, &path)? {
+                handle_dirs(
                     args,
-                    &mut dirs_to_analyze,
+                    &state.dirs_to_analyze,
+                    gitignore,
+                    &entry,
                     analyed_info,
                 )?;
             }
+        } else if metadata.is_file() {
+            if state.first_visit_file(args, &path, &metadata)? {
+                handle_files(args, state, analyed_info, &entry, &metadata)?;
+            } else if may_be_hard_linked(&metadata) {
+                handle_hard_link(args, analyed_info, &entry, &metadata)?;
+            }
+        } else if (args.count_symlinks() || args.follow_symlinks()) && metadata.is_symlink() {
+            handle_symlinks(entry, state, gitignore, args, analyed_info)?;
+        } else if let Some(special) = analyed_info.special_files_mut() {
+            special.add(metadata.file_type());
         }
-        if let Some(timer) = &mut timer {
-            timer.update();
-            if timer.ended() {
-                println!(
-                    "Update: {} found dirs, {} found files",
-                    analyed_info.found_dirs(),
-                    analyed_info.found_files()
-                );
-                timer.reset();
+        Ok(())
+    }
+
+    fn audit_permissions(
+        args: &Args,
+        analyed_info: &mut AnalyzedInfo,
+        entry: &DirEntry,
+        metadata: &fs::Metadata,
+        dir_uid: Option<u32>,
+    ) -> Result<()> {
+        if let (Some(audit), Some(mode), Some((uid, _))) = (
+            analyed_info.permission_audit_mut(),
+            permission_bits(metadata),
+            owner_ids(metadata),
+        ) {
+            audit.check(
+                &record_path(args, entry)?,
+                mode,
+                uid,
+                metadata.is_dir(),
+                dir_uid,
+            );
+        }
+        Ok(())
+    }
+
+    fn is_ignored(
+        args: &Args,
+        state: &SearchState,
+        gitignore: &Option<Arc<GitignoreStack>>,
+        entry: &DirEntry,
+        metadata: &fs::Metadata,
+    ) -> bool {
+        let path = entry.path();
+        if let Some(ignore) = &state.ignore {
+            if ignore.is_ignored(&path, metadata.is_dir()) {
+                return true;
+            }
+        }
+        if args.respect_gitignore() {
+            //git never tracks its own data
+            if entry.file_name() == ".git" {
+                return true;
+            }
+            if let Some(gitignore) = gitignore {
+                return gitignore.is_ignored(&path, metadata.is_dir());
             }
         }
+        false
     }
 
     fn handle_dirs(
         args: &Args,
-        dirs_to_analyze: &mut Vec<PathBuf>,
+        dirs_to_analyze: &WorkQueue<DirToAnalyze>,
+        gitignore: &Option<Arc<GitignoreStack>>,
         entry: &DirEntry,
         analyed_info: &mut AnalyzedInfo,
     ) -> anyhow::Result<()> {
-        handle_args(args, dirs_to_analyze, entry)?;
+        handle_args(args, dirs_to_analyze, gitignore, entry)?;
         *analyed_info.found_dirs_mut() += 1;
+        if let Some(dir_sizes) = analyed_info.dir_sizes_mut() {
+            dir_sizes.add_dir(&entry.path());
+        }
 
         fn handle_args(
             args: &Args,
-            dirs_to_analyze: &mut Vec<PathBuf>,
+            dirs_to_analyze: &WorkQueue<DirToAnalyze>,
+            gitignore: &Option<Arc<GitignoreStack>>,
             entry: &DirEntry,
         ) -> anyhow::Result<()> {
             if !args.no_recurse() {
-                dirs_to_analyze.push(entry.path());
+                dirs_to_analyze.push(DirToAnalyze {
+                    path: entry.path(),
+                    gitignore: gitignore.clone(),
+                });
             }
             if args.verbose() {
                 if args.full_path() {
@@ -110,19 +397,73 @@ fn search_dirs(args: &Args, analyed_info: &mut AnalyzedInfo) -> Result<()> {
 
     fn handle_files(
         args: &Args,
+        state: &SearchState,
         analyed_info: &mut AnalyzedInfo,
         entry: &DirEntry,
         metadata: &fs::Metadata,
     ) -> anyhow::Result<()> {
-        handle_file_args(args, analyed_info.file_info_mut(), entry, metadata)?;
+        let age = state.file_age(args, metadata)?;
+        let content_type = detect_type(args, analyed_info, entry)?;
+        //Files without extensions are kept under ""
+        let ext = state.extensions.extension(&entry.path());
+        if let Some(categories) = analyed_info.categories_mut() {
+            //The detected type is only used for files whose extension is not in a category
+            let category = match (state.categories.find(&ext), content_type) {
+                (Some(category), _) => category,
+                (None, Some(content_type)) => state.categories.category(content_type.name()),
+                (None, None) => OTHER_CATEGORY,
+            };
+            categories.add(category, record_size(args, metadata));
+        }
+        let ext = match content_type {
+            Some(content_type) => content_type.group_key(ext),
+            None => ext,
+        };
+        if let Some(totals) = analyed_info.snapshot_totals_mut() {
+            totals.add(&entry.path(), &ext, metadata.len());
+        }
+        handle_file_args(
+            args,
+            analyed_info.file_info_mut(),
+            ext,
+            entry,
+            metadata,
+            age,
+        )?;
         *analyed_info.found_files_mut() += 1;
         *analyed_info.total_bytes_mut() += metadata.len();
+        if let Some(allocated) = analyed_info.allocated_bytes_mut() {
+            *allocated += allocated_size(metadata);
+        }
+        if let Some(dir_sizes) = analyed_info.dir_sizes_mut() {
+            dir_sizes.add_file(&entry.path(), record_size(args, metadata));
+        }
+        if let Some(duplicates) = analyed_info.duplicates_mut() {
+            duplicates.add_candidate(record_path(args, entry)?, metadata.len());
+        }
+        if let Some(largest_files) = analyed_info.largest_files_mut() {
+            largest_files.add(FileTypeInfoRecords::new(
+                record_path(args, entry)?,
+                record_size(args, metadata),
+            ));
+        }
+        if let (Some(histogram), Some(age)) = (analyed_info.age_histogram_mut(), age) {
+            histogram.add(age.age_secs, metadata.len());
+        }
+        if let Some(sizes) = analyed_info.size_histogram_mut() {
+            sizes.add(record_size(args, metadata));
+        }
+        if let (Some(owners), Some((uid, gid))) = (analyed_info.owners_mut(), owner_ids(metadata)) {
+            owners.add(uid, gid, record_size(args, metadata));
+        }
 
         fn handle_file_args(
             args: &Args,
             map: Option<&mut HashMap<String, FileTypeInfo>>,
+            ext: FileExtension,
             entry: &DirEntry,
             metadata: &fs::Metadata,
+            age: Option<FileAge>,
         ) -> anyhow::Result<()> {
             if args.verbose() {
                 if args.full_path() {
@@ -133,44 +474,137 @@ fn search_dirs(args: &Args, analyed_info: &mut AnalyzedInfo) -> Result<()> {
             }
 
             if let Some(map) = map {
-                match entry.path().extension() {
-                    Some(ext) => {
-                        let ext = ext.to_os_string().to_string_lossy().to_string();
-                        add_file_info_to_map(args, ext, map, entry, metadata)?;
-                    }
-                    //Still want to keep info about files without extensions
-                    None => {
-                        let ext = "".to_string();
-                        add_file_info_to_map(args, ext, map, entry, metadata)?;
-                    }
-                }
+                add_file_info_to_map(args, ext, map, entry, metadata, age)?;
             }
             Ok(())
         }
         Ok(())
     }
 
+    ///Detects the type of the file from its content if --detect-type is set and records it if
+    ///its extension disagrees. Files that can't be read are recorded as errors and are
+    ///treated as unknown
+    fn detect_type(
+        args: &Args,
+        analyed_info: &mut AnalyzedInfo,
+        entry: &DirEntry,
+    ) -> anyhow::Result<Option<ContentType>> {
+        if !args.detect_type() {
+            return Ok(None);
+        }
+        let content_type = match ContentType::detect(&entry.path()) {
+            Ok(content_type) => content_type,
+            Err(e) => {
+                record_error(args, analyed_info, &entry.path(), e.into())?;
+                ContentType::Unknown
+            }
+        };
+        let ext = file_extension(&entry.path());
+        if content_type.disagrees_with(&ext) {
+            if args.verbose() {
+                println!(
+                    "extension does not match content: {}",
+                    record_path(args, entry)?.to_string_lossy()
+                );
+            }
+            if let Some(mismatches) = analyed_info.type_mismatches_mut() {
+                mismatches.push(TypeMismatch::new(
+                    record_path(args, entry)?,
+                    ext,
+                    content_type,
+                ));
+            }
+        }
+        Ok(Some(content_type))
+    }
+
+    ///Records a directory on another filesystem without looking inside it
+    fn handle_mount_point(
+        args: &Args,
+        analyed_info: &mut AnalyzedInfo,
+        entry: &DirEntry,
+    ) -> anyhow::Result<()> {
+        if args.verbose() {
+            println!(
+                "skipped mount point: {}",
+                record_path(args, entry)?.to_string_lossy()
+            );
+        }
+        if let Some(mount_points) = analyed_info.skipped_mount_points_mut() {
+            mount_points.push(record_path(args, entry)?);
+        }
+        Ok(())
+    }
+
+    ///Counts another link to a file that was already counted without counting the file again
+    fn handle_hard_link(
+        args: &Args,
+        analyed_info: &mut AnalyzedInfo,
+        entry: &DirEntry,
+        metadata: &fs::Metadata,
+    ) -> anyhow::Result<()> {
+        if args.verbose() {
+            println!("hard link: {}", record_path(args, entry)?.to_string_lossy());
+        }
+        *analyed_info.hard_links_mut() += 1;
+        *analyed_info.bytes_saved_by_hardlinks_mut() += metadata.len();
+        //Still needed so the link shows up with the others in the hard link sets
+        if let Some(duplicates) = analyed_info.duplicates_mut() {
+            duplicates.add_candidate(record_path(args, entry)?, metadata.len());
+        }
+        Ok(())
+    }
+
     ///Traverses and counts symlinks and if the target is not already counted counts it
     fn handle_symlinks(
         entry: DirEntry,
-        found_items: &mut Vec<FastPath>,
+        state: &SearchState,
+        gitignore: &Option<Arc<GitignoreStack>>,
         args: &Args,
-        dirs_to_analyze: &mut Vec<PathBuf>,
         analyed_info: &mut AnalyzedInfo,
     ) -> Result<(), anyhow::Error> {
-        let path = fs::read_link(entry.path())?;
-        let metadata = path.metadata()?;
+        let target = fs::read_link(entry.path())?;
+        //Relative targets are relative to the directory the symlink is in, not the current directory
+        let path = match entry.path().parent() {
+            Some(parent) => parent.join(&target),
+            None => target.clone(),
+        };
+        //The symlink itself was read so any failure to follow it is a problem with its target,
+        //e.g. it is missing, loops or goes through a file
+        let metadata = match path.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                if args.verbose() {
+                    println!(
+                        "broken symlink: {} -> {}",
+                        record_path(args, &entry)?.to_string_lossy(),
+                        target.to_string_lossy()
+                    );
+                }
+                if let Some(symlink) = analyed_info.found_symlinks_mut() {
+                    symlink.add_broken(BrokenSymlink::new(record_path(args, &entry)?, target));
+                }
+                return Ok(());
+            }
+        };
         if args.follow_symlinks() {
+            //a symlink to another filesystem is left out the same as a mount point,
             //don't look at entries that have been seen before
             //prevents following symlink loops and counting entries multiple times
-            if !found_items.contains(&FastPath::new(&path)) {
+            if metadata.is_dir() && state.crosses_mount_point(&path)? {
+                handle_mount_point(args, analyed_info, &entry)?;
+            } else if state.first_visit(args, &path)? {
                 if metadata.is_dir() {
-                    handle_dirs(args, dirs_to_analyze, &entry, analyed_info)?;
+                    handle_dirs(
+                        args,
+                        &state.dirs_to_analyze,
+                        gitignore,
+                        &entry,
+                        analyed_info,
+                    )?;
                 } else if metadata.is_file() {
-                    handle_files(args, analyed_info, &entry, &metadata)?;
+                    handle_files(args, state, analyed_info, &entry, &metadata)?;
                 }
-
-                found_items.push(FastPath::new(&path));
             }
         }
 
@@ -182,6 +616,8 @@ fn search_dirs(args: &Args, analyed_info: &mut AnalyzedInfo) -> Result<()> {
                     *symlink.dir_symlinks_mut() += 1;
                 } else if metadata.is_file() {
                     *symlink.file_symlinks_mut() += 1;
+                } else {
+                    *symlink.special_symlinks_mut() += 1;
                 }
                 *symlink.found_symlinks_mut() += 1;
             }
@@ -190,26 +626,7 @@ fn search_dirs(args: &Args, analyed_info: &mut AnalyzedInfo) -> Result<()> {
         Ok(())
     }
 
-    Ok(())
-}
-
-fn set_up_ignore_these(args: &Args) -> Result<Option<Vec<PathBuf>>, anyhow::Error> {
-    let ignore_these: Option<Vec<_>> = if let Some(s) = args.ignore_entries() {
-        let paths: Vec<_> = s.split(',').map(|s| s.trim()).map(PathBuf::from).collect();
-        for p in &paths {
-            let exists = p.try_exists()?;
-            if !exists {
-                eprintln!(
-                    "WARNING: Can't ignore \"{}\" because it doesn't exist",
-                    p.to_string_lossy()
-                );
-            }
-        }
-        Some(paths)
-    } else {
-        None
-    };
-    Ok(ignore_these)
+    Ok(analyed_info)
 }
 
 fn add_file_info_to_map(
@@ -218,38 +635,243 @@ fn add_file_info_to_map(
     map: &mut HashMap<String, FileTypeInfo>,
     entry: &DirEntry,
     metadata: &std::fs::Metadata,
+    age: Option<FileAge>,
 ) -> anyhow::Result<()> {
-    let t = map.entry(extension).or_insert(FileTypeInfo::new(
-        0,
-        0,
-        FileTypeInfoRecords::default(),
-        FileTypeInfoRecords::new(PathBuf::default(), u64::MAX),
-    ));
+    let t = map.entry(extension).or_insert_with(|| {
+        let mut t = FileTypeInfo::new(
+            0,
+            0,
+            FileTypeInfoRecords::default(),
+            FileTypeInfoRecords::new(PathBuf::default(), u64::MAX),
+        );
+        t.set_largest_files(args.top().map(LargestFiles::new));
+        t.set_allocated_bytes(args.allocated_size().then_some(0));
+        if args.age() {
+            t.set_age_histogram(Some(AgeHistogram::new(args.age_time(), args.age_buckets())));
+        }
+        if args.size_histogram() {
+            t.set_size_histogram(Some(SizeHistogram::new()));
+        }
+        t
+    });
     *t.num_files_mut() += 1;
     *t.size_in_bytes_mut() += metadata.len();
-    let path = if args.full_path() {
-        path::absolute(entry.path())?
-    } else {
-        entry.path()
-    };
-    if metadata.len() > t.largest_file().size() {
-        t.set_largest_file(FileTypeInfoRecords::new(path.clone(), metadata.len()));
+    if let Some(allocated) = t.allocated_bytes_mut() {
+        *allocated += allocated_size(metadata);
+    }
+    t.update_records(FileTypeInfoRecords::new(
+        record_path(args, entry)?,
+        record_size(args, metadata),
+    ));
+    if let Some(sizes) = t.size_histogram_mut() {
+        sizes.add(record_size(args, metadata));
+    }
+    if let Some(age) = age {
+        t.update_age(
+            FileTimeRecord::new(record_path(args, entry)?, age.time),
+            age.age_secs,
+            metadata.len(),
+        );
+    }
+    Ok(())
+}
+
+///The time of `metadata` in seconds since the Unix epoch
+fn file_time(age_time: AgeTime, metadata: &fs::Metadata) -> Result<i64> {
+    Ok(match age_time {
+        AgeTime::Modified => unix_secs(metadata.modified()?),
+        AgeTime::Accessed => unix_secs(metadata.accessed()?),
+        AgeTime::Changed => changed_time(metadata)?,
+    })
+}
+
+///When the contents or metadata were last changed, Windows doesn't track that so the time the
+///file was created is used instead
+fn changed_time(metadata: &fs::Metadata) -> Result<i64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Ok(metadata.ctime())
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(unix_secs(metadata.created()?))
+    }
+}
+
+///Seconds since the Unix epoch, negative if `time` is before it
+fn unix_secs(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+///Id of the device or volume the object at `path` is on
+fn device_id(path: &Path) -> Result<u64> {
+    Ok(match file_id::get_file_id(path)? {
+        FileId::Inode { device_id, .. } => device_id,
+        FileId::LowRes {
+            volume_serial_number,
+            ..
+        } => volume_serial_number as u64,
+        FileId::HighRes {
+            volume_serial_number,
+            ..
+        } => volume_serial_number,
+    })
+}
+
+///If the file could have more than one hard link, on platforms where the link count isn't known
+///every file could be
+fn may_be_hard_linked(metadata: &fs::Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.nlink() > 1
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        true
+    }
+}
+
+///Space the file takes up on disk, on platforms where that isn't known it is the size of the file
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        //st_blocks is always in 512 byte units no matter the block size of the filesystem
+        metadata.blocks() * 512
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.len()
+    }
+}
+
+///uid and gid of the owner, `None` on platforms without them
+fn owner_ids(metadata: &fs::Metadata) -> Option<(u32, u32)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some((metadata.uid(), metadata.gid()))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
+    }
+}
+
+///st_mode including the file type, `None` on platforms without it
+fn permission_bits(metadata: &fs::Metadata) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(metadata.mode())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = metadata;
+        None
     }
-    if metadata.len() < t.smallest_file().size() {
-        t.set_smallest_file(FileTypeInfoRecords::new(path, metadata.len()));
+}
+
+///Size of the file used for percentages and records about notable files
+fn record_size(args: &Args, metadata: &fs::Metadata) -> u64 {
+    match args.size_basis() {
+        SizeBasis::Apparent => metadata.len(),
+        SizeBasis::Allocated => allocated_size(metadata),
+    }
+}
+
+///Records that `path` could not be analyzed so the rest of the tree can still be analyzed,
+///with --strict `err` is returned instead
+fn record_error(
+    args: &Args,
+    analyed_info: &mut AnalyzedInfo,
+    path: &Path,
+    err: anyhow::Error,
+) -> Result<()> {
+    if args.strict() {
+        return Err(err);
     }
+    if args.verbose() {
+        println!("error: {}: {err}", path.to_string_lossy());
+    }
+    analyed_info
+        .errors_mut()
+        .push(EntryError::from_error(path.to_path_buf(), &err));
     Ok(())
 }
 
+///Path of `entry` as it should be shown in records about notable files
+fn record_path(args: &Args, entry: &DirEntry) -> anyhow::Result<PathBuf> {
+    if args.full_path() {
+        Ok(path::absolute(entry.path())?)
+    } else {
+        Ok(entry.path())
+    }
+}
+
 ///Configures `AnalyzedInfo` based on the `Args` given
 fn set_up_anaylzed_info(args: &Args) -> AnalyzedInfo {
     let mut out = AnalyzedInfo::default();
+    out.set_size_basis(args.size_basis());
+    out.set_size_units(args.human_readable());
+    out.set_sort(args.sort_by(), args.sort_order());
+    out.set_allocated_bytes(args.allocated_size().then_some(0));
     if args.file_info() {
         out.set_file_info(Some(HashMap::default()));
     }
     if args.count_symlinks() {
         out.set_found_symlinks(Some(SymlinkInfo::default()));
     }
+    if args.age() {
+        out.set_age_histogram(Some(AgeHistogram::new(args.age_time(), args.age_buckets())));
+    }
+    if args.size_histogram() {
+        out.set_size_histogram(Some(SizeHistogram::new()));
+    }
+    if args.categories() {
+        out.set_categories(Some(CategoryInfo::default()));
+    }
+    if args.by_owner() {
+        out.set_owners(Some(OwnerInfo::default()));
+    }
+    if args.audit_permissions() {
+        out.set_permission_audit(Some(PermissionAudit::default()));
+    }
+    if args.detect_type() {
+        out.set_type_mismatches(Some(Vec::new()));
+    }
+    if args.special_files() {
+        out.set_special_files(Some(SpecialFileInfo::default()));
+    }
+    if args.dir_sizes() {
+        out.set_dir_sizes(Some(DirSizes::new(
+            args.path_to_analyze().clone(),
+            args.depth(),
+        )));
+    }
+    out.set_largest_files(args.top().map(LargestFiles::new));
+    if args.find_duplicates() {
+        out.set_duplicates(Some(DuplicateInfo::default()));
+    }
+    if has_ignore_options(args) {
+        out.set_ignored_entries(Some(0));
+    }
+    if args.one_file_system() {
+        out.set_skipped_mount_points(Some(Vec::new()));
+    }
+    if args.save_snapshot().is_some() {
+        out.set_snapshot_totals(Some(SnapshotTotals::new(
+            args.path_to_analyze().clone(),
+            args.snapshot_files(),
+        )));
+    }
     out
 }
 
@@ -257,12 +879,44 @@ fn set_up_anaylzed_info(args: &Args) -> AnalyzedInfo {
 mod tests {
     use std::{path::PathBuf, str::FromStr};
 
-    use crate::FileExtension;
+    use crate::{
+        format_size, AgeLimit, DirSize, DuplicateSet, ExtensionAlias, ExtensionNormalization,
+        FileChange, FileExtension, SizeHistogram, SizeUnits, Snapshot, SnapshotDiff, SortBy,
+        SortOrder, Totals, TotalsChange,
+    };
 
     use super::*;
 
     const TEST_DIR: &str = "../test/";
 
+    ///A fresh directory under the system temp dir that is removed when dropped, so it is cleaned
+    ///up even if the test fails
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("dira_{name}_{}", std::process::id()));
+            //Left over from an earlier run that was killed
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
     #[test]
     fn analyze_default_settings() {
         let test_args = Args::new(
@@ -450,7 +1104,8 @@ mod tests {
             false,
         );
         let res = analyze(&test_args).unwrap();
-        let expected = AnalyzedInfo::new(2, 5, None, None, 235);
+        let mut expected = AnalyzedInfo::new(2, 5, None, None, 235);
+        expected.set_ignored_entries(Some(2));
         assert_eq!(res, expected);
     }
 
@@ -541,7 +1196,8 @@ mod tests {
             info.calculate_percentages(total_bytes, total_files);
         }
         let res = analyze(&test_args).unwrap();
-        let expected = AnalyzedInfo::new(2, 5, None, Some(hash_map), 235);
+        let mut expected = AnalyzedInfo::new(2, 5, None, Some(hash_map), 235);
+        expected.set_ignored_entries(Some(2));
         assert_eq!(res, expected);
     }
 
@@ -594,4 +1250,1283 @@ mod tests {
         let expected = AnalyzedInfo::new(6, 8, None, None, 432);
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn analyze_multiple_threads_matches_single_thread() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let single = analyze(&test_args).unwrap();
+        test_args.set_threads(4);
+        let multi = analyze(&test_args).unwrap();
+        assert_eq!(single, multi);
+    }
+
+    #[test]
+    fn analyze_dir_sizes() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_dir_sizes(true);
+        test_args.set_depth(1);
+        test_args.set_threads(2);
+        let res = analyze(&test_args).unwrap();
+        let dir_sizes = res.dir_sizes().unwrap();
+        let root = PathBuf::from_str(TEST_DIR).unwrap();
+        assert_eq!(dir_sizes.get(&root), Some(&DirSize::new(432, 7, 4)));
+        assert_eq!(
+            dir_sizes.get(&root.join("folder2")),
+            Some(&DirSize::new(16, 2, 2))
+        );
+        assert_eq!(
+            dir_sizes.get(&root.join("folder2/folder3/folder4")),
+            Some(&DirSize::new(9, 1, 0))
+        );
+        let report: Vec<_> = dir_sizes
+            .report()
+            .iter()
+            .map(|r| (r.path.to_path_buf(), r.depth))
+            .collect();
+        assert_eq!(
+            report,
+            vec![
+                (root.clone(), 0),
+                (root.join("folder2"), 1),
+                (root.join("folder1"), 1)
+            ]
+        );
+
+        //Sizes follow --size-basis the same as the totals
+        test_args.set_size_basis(SizeBasis::Allocated);
+        let res = analyze(&test_args).unwrap();
+        let dir_sizes = res.dir_sizes().unwrap();
+        assert_eq!(
+            dir_sizes.get(&root).map(|size| size.bytes()),
+            res.allocated_bytes()
+        );
+        let folder4 = root.join("folder2/folder3/folder4");
+        assert_eq!(
+            dir_sizes.get(&folder4).map(|size| size.bytes()),
+            Some(allocated_size(
+                &fs::metadata(folder4.join("deepfile1.txt")).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn analyze_top_largest_files() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_top(Some(3));
+        test_args.set_threads(2);
+        let res = analyze(&test_args).unwrap();
+        let root = PathBuf::from_str(TEST_DIR).unwrap();
+        assert_eq!(
+            res.largest_files().unwrap().sorted(),
+            vec![
+                FileTypeInfoRecords::new(root.join("file3.rtf"), 196),
+                FileTypeInfoRecords::new(root.join("file4.zip"), 188),
+                FileTypeInfoRecords::new(root.join("file2.txt"), 14),
+            ]
+        );
+        //Ties are broken by path
+        let txt = res.file_info().unwrap()["txt"].largest_files().unwrap();
+        assert_eq!(
+            txt.sorted(),
+            vec![
+                FileTypeInfoRecords::new(root.join("file2.txt"), 14),
+                FileTypeInfoRecords::new(root.join("file1.txt"), 9),
+                FileTypeInfoRecords::new(root.join("folder1/file5.txt"), 9),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn analyze_allocated_size() {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let apparent = analyze(&test_args).unwrap();
+        assert_eq!(apparent.allocated_bytes(), None);
+
+        test_args.set_size_basis(SizeBasis::Allocated);
+        test_args.set_top(Some(1));
+        let res = analyze(&test_args).unwrap();
+        //The size of each file is still tracked as is
+        assert_eq!(res.total_bytes(), apparent.total_bytes());
+        let file_info = res.file_info().unwrap();
+        let allocated: u64 = file_info
+            .values()
+            .map(|info| info.allocated_bytes().unwrap())
+            .sum();
+        assert_eq!(res.allocated_bytes(), Some(allocated));
+        //Records are by allocated size
+        let largest = &res.largest_files().unwrap().sorted()[0];
+        let metadata = fs::metadata(largest.path()).unwrap();
+        assert_eq!(largest.size(), metadata.blocks() * 512);
+        for info in file_info.values() {
+            let metadata = fs::metadata(info.smallest_file().path()).unwrap();
+            assert_eq!(info.smallest_file().size(), metadata.blocks() * 512);
+        }
+    }
+
+    #[test]
+    fn analyze_ignore_globs() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        //folder3/ only matches the directory, *.zip matches at any depth
+        test_args.set_ignore_globs(vec!["folder3/".to_string(), "*.zip".to_string()]);
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(2, 5, None, None, 235);
+        expected.set_ignored_entries(Some(2));
+        assert_eq!(res, expected);
+
+        test_args.set_ignore_globs(vec!["/file*".to_string(), "**/folder4".to_string()]);
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(3, 2, None, None, 16);
+        expected.set_ignored_entries(Some(5));
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn analyze_ignore_regexes() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_ignore_regexes(vec![
+            r"^folder2/.*\.txt$".to_string(),
+            r"\.(rtf|zip)$".to_string(),
+        ]);
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(4, 3, None, None, 32);
+        expected.set_ignored_entries(Some(4));
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn analyze_respect_gitignore() {
+        let mut test_args = Args::new(
+            PathBuf::from_str("../test_gitignore/").unwrap(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_respect_gitignore(true);
+        //b.log, build/, sub/secret.txt and sub/d.txt are ignored
+        //keep.log and sub/c.log are re-included by negated rules and sub/build is not a directory
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(1, 7, None, None, 66);
+        expected.set_ignored_entries(Some(4));
+        assert_eq!(res, expected);
+
+        test_args.set_ignore_globs(vec!["*.log".to_string()]);
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(1, 5, None, None, 61);
+        expected.set_ignored_entries(Some(6));
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn analyze_respect_gitignore_subdir() {
+        let dir = TempDir::new("gitignore_subdir");
+        fs::create_dir_all(dir.join(".git/info")).unwrap();
+        fs::create_dir_all(dir.join("sub/deeper")).unwrap();
+        fs::write(dir.join(".git/info/exclude"), "*.tmp\n").unwrap();
+        fs::write(dir.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.join("sub/.gitignore"), "!keep.log\n").unwrap();
+        for name in ["a.txt", "b.log", "keep.log", "c.tmp", "deeper/d.log"] {
+            fs::write(dir.join("sub").join(name), "12345").unwrap();
+        }
+
+        let mut test_args = Args::new(
+            dir.join("sub"),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_respect_gitignore(true);
+        //The rules above sub still apply, b.log, c.tmp and deeper/d.log are ignored
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(1, 3, None, None, 20);
+        expected.set_ignored_entries(Some(3));
+        assert_eq!(res, expected);
+
+        //Outside of a repository the ignore files above the path are not used
+        fs::remove_dir_all(dir.join(".git")).unwrap();
+        let res = analyze(&test_args).unwrap();
+        let mut expected = AnalyzedInfo::new(1, 6, None, None, 35);
+        expected.set_ignored_entries(Some(0));
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn analyze_find_duplicates() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_find_duplicates(true);
+        test_args.set_threads(2);
+        let res = analyze(&test_args).unwrap();
+        let duplicates = res.duplicates().unwrap();
+        let root = PathBuf::from_str(TEST_DIR).unwrap();
+        assert_eq!(
+            duplicates.duplicate_sets(),
+            &[DuplicateSet::new(
+                9,
+                18,
+                vec![
+                    root.join("file1.txt"),
+                    root.join("folder1/file5.txt"),
+                    root.join("folder2/folder3/folder4/deepfile1.txt"),
+                ]
+            )]
+        );
+        assert!(duplicates.hard_link_sets().is_empty());
+        assert_eq!(duplicates.wasted_bytes(), 18);
+    }
+
+    #[test]
+    fn analyze_hard_links() {
+        let dir = TempDir::new("hard_links");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "0123456789").unwrap();
+        fs::write(dir.join("c.txt"), "01234").unwrap();
+        fs::hard_link(dir.join("a.txt"), dir.join("b.txt")).unwrap();
+        fs::hard_link(dir.join("a.txt"), dir.join("sub/d.txt")).unwrap();
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_find_duplicates(true);
+        test_args.set_threads(2);
+        let res = analyze(&test_args).unwrap();
+
+        assert_eq!(res.found_files(), 2);
+        assert_eq!(res.total_bytes(), 15);
+        assert_eq!(res.hard_links(), 2);
+        assert_eq!(res.bytes_saved_by_hardlinks(), 20);
+        let duplicates = res.duplicates().unwrap();
+        assert!(duplicates.duplicate_sets().is_empty());
+        assert_eq!(
+            duplicates.hard_link_sets(),
+            &[DuplicateSet::new(
+                10,
+                0,
+                vec![dir.join("a.txt"), dir.join("b.txt"), dir.join("sub/d.txt")]
+            )]
+        );
+    }
+
+    #[test]
+    fn analyze_one_file_system() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let mut expected = analyze(&test_args).unwrap();
+        expected.set_skipped_mount_points(Some(Vec::new()));
+        test_args.set_one_file_system(true);
+        test_args.set_threads(2);
+        let res = analyze(&test_args).unwrap();
+        //Nothing in the test directory is on another filesystem
+        assert_eq!(res, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn analyze_errors() {
+        let dir = TempDir::new("errors");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "0123456789").unwrap();
+        fs::write(dir.join("sub/b.txt"), "01234").unwrap();
+        //A directory whose path is longer than the OS allows can't be read. Each half is created
+        //with a path short enough to be allowed and then one is moved into the other
+        let name = "d".repeat(200);
+        let (outer, inner) = (dir.join("deep"), dir.join("inner"));
+        fs::create_dir_all(outer.join([name.as_str(); 12].join("/"))).unwrap();
+        fs::create_dir_all(inner.join([name.as_str(); 12].join("/"))).unwrap();
+        fs::rename(
+            &inner,
+            outer.join([name.as_str(); 12].join("/")).join("inner"),
+        )
+        .unwrap();
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            true,
+            false,
+        );
+        let res = analyze(&test_args);
+        test_args.set_strict(true);
+        let strict_res = analyze(&test_args);
+
+        //The rest of the tree is still analyzed
+        let res = res.unwrap();
+        assert_eq!(res.found_files(), 2);
+        assert_eq!(res.total_bytes(), 15);
+        assert_eq!(res.errors().len(), 1);
+        assert!(res.errors()[0].path().starts_with(dir.join("deep")));
+        assert!(strict_res.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn analyze_broken_symlinks() {
+        use crate::BrokenSymlink;
+        use std::os::unix::{fs::symlink, net::UnixListener};
+
+        let dir = TempDir::new("broken_symlinks");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "0123456789").unwrap();
+        //Relative targets are resolved from the directory the symlink is in
+        symlink("../a.txt", dir.join("sub/a_link")).unwrap();
+        symlink("sub", dir.join("sub_link")).unwrap();
+        symlink("missing.txt", dir.join("sub/dangling")).unwrap();
+        //Broken without the target being missing
+        symlink("loop", dir.join("loop")).unwrap();
+        symlink("a.txt/child", dir.join("through_file")).unwrap();
+        let _socket = UnixListener::bind(dir.join("socket")).unwrap();
+        symlink("socket", dir.join("socket_link")).unwrap();
+
+        let test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            true,
+            false,
+        );
+        let res = analyze(&test_args).unwrap();
+
+        assert!(res.errors().is_empty());
+        let symlinks = res.found_symlinks().unwrap();
+        assert_eq!(symlinks.found_symlinks(), 6);
+        assert_eq!(symlinks.file_symlinks(), 1);
+        assert_eq!(symlinks.dir_symlinks(), 1);
+        assert_eq!(symlinks.special_symlinks(), 1);
+        assert_eq!(symlinks.broken_symlinks(), 3);
+        assert_eq!(
+            symlinks.broken(),
+            &[
+                BrokenSymlink::new(dir.join("loop"), PathBuf::from("loop")),
+                BrokenSymlink::new(dir.join("sub/dangling"), PathBuf::from("missing.txt")),
+                BrokenSymlink::new(dir.join("through_file"), PathBuf::from("a.txt/child")),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn analyze_special_files() {
+        use std::os::unix::net::UnixListener;
+
+        let dir = TempDir::new("special_files");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "0123456789").unwrap();
+        let _socket = UnixListener::bind(dir.join("socket")).unwrap();
+        let _sub_socket = UnixListener::bind(dir.join("sub/socket")).unwrap();
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let res = analyze(&test_args);
+        test_args.set_special_files(true);
+        test_args.set_threads(2);
+        let special_res = analyze(&test_args);
+
+        let mut expected = AnalyzedInfo::new(1, 1, None, None, 10);
+        assert_eq!(res.unwrap(), expected);
+        expected.set_special_files(Some(SpecialFileInfo::new(0, 2, 0, 0)));
+        assert_eq!(special_res.unwrap(), expected);
+    }
+
+    #[test]
+    fn analyze_age() {
+        let dir = TempDir::new("age");
+        let now = SystemTime::now();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+        for (name, contents, age) in [
+            ("new.txt", "0123456789", std::time::Duration::ZERO),
+            ("old.txt", "01234", day * 2),
+            ("older.log", "012", day * 400),
+        ] {
+            let file = fs::File::create(dir.join(name)).unwrap();
+            std::io::Write::write_all(&mut &file, contents.as_bytes()).unwrap();
+            file.set_modified(now - age).unwrap();
+        }
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_age(true);
+        let res = analyze(&test_args).unwrap();
+
+        let counts = |histogram: &AgeHistogram| -> Vec<_> {
+            histogram
+                .buckets()
+                .iter()
+                .map(|b| (b.label().to_string(), b.files(), b.bytes()))
+                .collect()
+        };
+        let bucket = |label: &str, files, bytes| (label.to_string(), files, bytes);
+        assert_eq!(
+            counts(res.age_histogram().unwrap()),
+            vec![
+                bucket("<1d", 1, 10),
+                bucket("<1w", 1, 5),
+                bucket("<1m", 0, 0),
+                bucket("<1y", 0, 0),
+                bucket(">=1y", 1, 3),
+            ]
+        );
+        let txt = &res.file_info().unwrap()["txt"];
+        assert_eq!(txt.oldest_file().unwrap().path(), dir.join("old.txt"));
+        assert_eq!(txt.newest_file().unwrap().path(), dir.join("new.txt"));
+        assert_eq!(
+            counts(txt.age_histogram().unwrap())[..2],
+            [bucket("<1d", 1, 10), bucket("<1w", 1, 5)]
+        );
+    }
+
+    #[test]
+    fn parse_age_limit() {
+        assert_eq!(
+            AgeLimit::from_str("2w").unwrap().secs(),
+            2 * 7 * 24 * 60 * 60
+        );
+        assert!(AgeLimit::from_str("2").is_err());
+        assert!(AgeLimit::from_str("w").is_err());
+        //Too many seconds to fit in a u64
+        assert!(AgeLimit::from_str("9999999999999999y").is_err());
+    }
+
+    #[test]
+    fn analyze_size_histogram() {
+        let dir = TempDir::new("size_histogram");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        for (name, size) in [
+            ("empty.txt", 0),
+            ("small.txt", 10),
+            ("a.log", 2000),
+            ("sub/b.log", 2000),
+            ("sub/big.bin", 70000),
+        ] {
+            fs::write(dir.join(name), vec![b'a'; size]).unwrap();
+        }
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_size_histogram(true);
+        test_args.set_threads(2);
+        let res = analyze(&test_args).unwrap();
+
+        let sizes = res.size_histogram().unwrap();
+        let counts: Vec<_> = sizes
+            .buckets()
+            .iter()
+            .map(|b| (b.label(), b.files(), b.bytes()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("0", 1, 0),
+                ("<1K", 1, 10),
+                ("<4K", 2, 4000),
+                ("<64K", 0, 0),
+                ("<1M", 1, 70000),
+                ("<100M", 0, 0),
+                (">=100M", 0, 0),
+            ]
+        );
+        assert_eq!(sizes.mean(), Some(74010.0 / 5.0));
+        //The median is estimated to within 1% of the real one
+        let close = |median: Option<f64>, real: f64| (median.unwrap() - real).abs() <= real * 0.01;
+        assert!(close(sizes.median(), 2000.0));
+        let txt = res.file_info().unwrap()["txt"].size_histogram().unwrap();
+        assert_eq!(txt.mean(), Some(5.0));
+        assert!(close(txt.median(), 5.0));
+
+        //Even when the range the middle file is in has much larger files
+        let mut sizes = SizeHistogram::new();
+        for size in [
+            1024,
+            1024,
+            4000,
+            2,
+            90 * 1024 * 1024,
+            2 * 1024 * 1024,
+            1500 * 1024,
+        ] {
+            sizes.add(size);
+        }
+        sizes.finish();
+        assert!(close(sizes.median(), 4000.0));
+        let mut sizes = SizeHistogram::new();
+        for size in [1024, 1024, 4000] {
+            sizes.add(size);
+        }
+        sizes.finish();
+        assert!(close(sizes.median(), 1024.0));
+    }
+
+    #[test]
+    fn human_readable_sizes() {
+        assert_eq!(format_size(432, None), "432 bytes");
+        assert_eq!(format_size(999, Some(SizeUnits::Si)), "999 B");
+        assert_eq!(format_size(1500, Some(SizeUnits::Si)), "1.5 kB");
+        assert_eq!(format_size(1536, Some(SizeUnits::Iec)), "1.5 KiB");
+        assert_eq!(format_size(3 << 30, Some(SizeUnits::Iec)), "3.0 GiB");
+        assert_eq!(format_size(u64::MAX, Some(SizeUnits::Si)), "18.4 EB");
+
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let res = analyze(&test_args).unwrap().to_string();
+        //Without --human-readable the output is the same as before it was added
+        assert!(res.contains("Total bytes: 432 bytes"));
+        assert!(res.contains("Size: 188\n"));
+        assert!(res.contains("Size in bytes for this type: 188"));
+        test_args.set_human_readable(Some(SizeUnits::Iec));
+        let res = analyze(&test_args).unwrap().to_string();
+        assert!(res.contains("Total size: 432 B"));
+        assert!(res.contains("Size: 188 B"));
+    }
+
+    #[test]
+    fn sorted_file_info() {
+        let mut test_args = Args::new(
+            PathBuf::from_str(TEST_DIR).unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let order = |args: &Args| -> Vec<String> {
+            let res = analyze(args).unwrap();
+            res.sorted_file_info()
+                .unwrap()
+                .into_iter()
+                .map(|(ext, _)| ext.clone())
+                .collect()
+        };
+        assert_eq!(order(&test_args), ["rtf", "zip", "txt"]);
+        test_args.set_sort_by(SortBy::Name);
+        assert_eq!(order(&test_args), ["rtf", "txt", "zip"]);
+        test_args.set_sort_order(Some(SortOrder::Desc));
+        assert_eq!(order(&test_args), ["zip", "txt", "rtf"]);
+        //rtf and zip both have 1 file so they are ordered by name
+        test_args.set_sort_by(SortBy::Count);
+        assert_eq!(order(&test_args), ["txt", "rtf", "zip"]);
+        test_args.set_sort_order(Some(SortOrder::Asc));
+        assert_eq!(order(&test_args), ["rtf", "zip", "txt"]);
+    }
+
+    #[test]
+    fn snapshot_diff() {
+        let dir = TempDir::new("snapshot");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "aaaa").unwrap();
+        fs::write(dir.join("gone.txt"), "x").unwrap();
+        fs::write(dir.join("sub/b.log"), "bb").unwrap();
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let snapshot_path = dir.with_extension("snap");
+        test_args.set_save_snapshot(Some(snapshot_path.clone()));
+        test_args.set_snapshot_files(true);
+        let snapshot = |args: &Args| -> Result<Snapshot> {
+            Snapshot::new(args.path_to_analyze(), &analyze(args)?)
+        };
+        let old = snapshot(&test_args).and_then(|old| {
+            old.save(&snapshot_path)?;
+            Snapshot::load(&snapshot_path)
+        });
+        fs::write(dir.join("a.txt"), "aaaaaaaaaaa").unwrap();
+        fs::remove_file(dir.join("gone.txt")).unwrap();
+        fs::write(dir.join("sub/new.bin"), "new!!").unwrap();
+        let new = snapshot(&test_args);
+        test_args.set_snapshot_files(false);
+        let without_files = snapshot(&test_args);
+        test_args.set_extension_aliases(vec!["bin=txt".parse().unwrap()]);
+        let aliased = snapshot(&test_args);
+        fs::remove_file(&snapshot_path).unwrap();
+        let (old, new, without_files) = (old.unwrap(), new.unwrap(), without_files.unwrap());
+
+        assert_eq!(old.total(), Totals::new(3, 7));
+        assert_eq!(old.dirs()["sub"], Totals::new(1, 2));
+        let diff = SnapshotDiff::new(&old, &new);
+        assert_eq!(diff.total().bytes_change(), 11);
+        let changes = |changes: &[TotalsChange]| -> Vec<(String, i64, i64)> {
+            changes
+                .iter()
+                .map(|c| (c.name().to_string(), c.files_change(), c.bytes_change()))
+                .collect()
+        };
+        assert_eq!(
+            changes(diff.extensions()),
+            [("txt".to_string(), -1, 6), ("bin".to_string(), 1, 5)]
+        );
+        assert_eq!(
+            changes(diff.dirs()),
+            [(".".to_string(), 0, 11), ("sub".to_string(), 1, 5)]
+        );
+        let files = diff.files().unwrap();
+        let paths = |changes: &[FileChange]| -> Vec<String> {
+            changes.iter().map(|c| c.path().to_string()).collect()
+        };
+        assert_eq!(paths(files.added()), ["sub/new.bin"]);
+        assert_eq!(paths(files.removed()), ["gone.txt"]);
+        assert_eq!(paths(files.grown()), ["a.txt"]);
+        assert_eq!(files.grown()[0].size_change(), 7);
+
+        let diff = SnapshotDiff::new(&old, &without_files);
+        assert!(diff.files().is_none());
+        assert_eq!(diff.extensions().len(), 2);
+
+        //Extensions are grouped the same as in file_info
+        let aliased = aliased.unwrap();
+        assert_eq!(
+            aliased.extensions().keys().collect::<Vec<_>>(),
+            ["log", "txt"]
+        );
+        assert_eq!(aliased.extensions()["txt"], Totals::new(2, 16));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn analyze_by_owner() {
+        use std::os::unix::fs::MetadataExt;
+
+        let dir = TempDir::new("by_owner");
+        fs::write(dir.join("a.txt"), "aaaa").unwrap();
+        fs::write(dir.join("b.txt"), "bb").unwrap();
+        let metadata = fs::metadata(dir.join("a.txt")).unwrap();
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_by_owner(true);
+        let res = analyze(&test_args).unwrap();
+
+        let owners = res.owners().unwrap();
+        assert_eq!(owners.users().len(), 1);
+        assert_eq!(owners.users()[0].id(), metadata.uid());
+        assert_eq!(owners.users()[0].totals(), Totals::new(2, 6));
+        assert_eq!(owners.groups()[0].id(), metadata.gid());
+
+        //No account is expected to have an id this large
+        let mut owners = OwnerInfo::default();
+        owners.add(metadata.uid(), u32::MAX - 1, 1);
+        owners.add(u32::MAX - 1, u32::MAX - 1, 10);
+        owners.finish();
+        assert!(owners.users()[0].orphaned());
+        assert_eq!(owners.users()[0].name(), None);
+        assert_eq!(owners.users()[1].id(), metadata.uid());
+        assert!(!owners.users()[1].orphaned());
+        assert_eq!(owners.groups()[0].totals(), Totals::new(2, 11));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn audit_permissions() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        use crate::PermissionIssue;
+
+        let dir = TempDir::new("audit");
+        fs::create_dir_all(dir.join("open")).unwrap();
+        fs::create_dir_all(dir.join("tmp")).unwrap();
+        for (name, mode) in [
+            ("plain.txt", 0o644),
+            ("writable.txt", 0o666),
+            ("suid", 0o4755),
+            ("sgid", 0o2755),
+            ("sticky.txt", 0o1644),
+        ] {
+            fs::write(dir.join(name), "a").unwrap();
+            fs::set_permissions(dir.join(name), fs::Permissions::from_mode(mode)).unwrap();
+        }
+        fs::set_permissions(dir.join("open"), fs::Permissions::from_mode(0o777)).unwrap();
+        fs::set_permissions(dir.join("tmp"), fs::Permissions::from_mode(0o1777)).unwrap();
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_audit_permissions(true);
+        let res = analyze(&test_args).unwrap();
+
+        let found: Vec<_> = res
+            .permission_audit()
+            .unwrap()
+            .findings()
+            .iter()
+            .map(|f| {
+                (
+                    f.issue(),
+                    f.path().file_name().unwrap().to_owned(),
+                    f.mode(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (PermissionIssue::WorldWritable, "open".into(), 0o777),
+                (PermissionIssue::WorldWritable, "tmp".into(), 0o1777),
+                (PermissionIssue::WorldWritable, "writable.txt".into(), 0o666),
+                (PermissionIssue::Setuid, "suid".into(), 0o4755),
+                (PermissionIssue::Setgid, "sgid".into(), 0o2755),
+                (PermissionIssue::MissingStickyBit, "open".into(), 0o777),
+                (PermissionIssue::StickyFile, "sticky.txt".into(), 0o1644),
+            ]
+        );
+
+        //Every file above has the same owner as its directory
+        let uid = fs::metadata(std::env::temp_dir()).unwrap().uid();
+        let mut audit = PermissionAudit::default();
+        audit.check(Path::new("mine"), 0o100644, uid, false, Some(uid));
+        audit.check(Path::new("theirs"), 0o100644, uid, false, Some(uid + 1));
+        audit.check(Path::new("their_dir"), 0o40755, uid, true, Some(uid + 1));
+        audit.finish();
+        let found: Vec<_> = audit
+            .findings()
+            .iter()
+            .map(|f| (f.issue(), f.path().to_owned(), f.parent_uid()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    PermissionIssue::OwnerDiffersFromParent,
+                    PathBuf::from("their_dir"),
+                    Some(uid + 1)
+                ),
+                (
+                    PermissionIssue::OwnerDiffersFromParent,
+                    PathBuf::from("theirs"),
+                    Some(uid + 1)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_type() {
+        let dir = TempDir::new("detect_type");
+        let elf: &[u8] = b"\x7fELF\x02\x01\x01\0\0";
+        let png: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        for (name, contents) in [
+            ("prog", elf),
+            ("libc.so.6", elf),
+            ("photo.png", png),
+            ("corrupt.png", b"\0\x01\x02\xfe"),
+            ("renamed.txt", png),
+            ("fake.PNG", b"just some text"),
+            ("notes.txt", "text with ünïcode".as_bytes()),
+            ("report.docx", b"PK\x03\x04\x14\0"),
+            ("data.xyz", b"\0\x01\x02\xfe"),
+            ("empty.png", b""),
+        ] {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_detect_type(true);
+        let res = analyze(&test_args).unwrap();
+
+        let mut groups: Vec<_> = res
+            .file_info()
+            .unwrap()
+            .iter()
+            .map(|(key, info)| (key.as_str(), info.num_files()))
+            .collect();
+        groups.sort();
+        assert_eq!(
+            groups,
+            vec![
+                ("elf", 2),
+                ("empty", 1),
+                ("png", 2),
+                ("text", 2),
+                ("unknown:png", 1),
+                ("unknown:xyz", 1),
+                ("zip", 1)
+            ]
+        );
+
+        let mismatches: Vec<_> = res
+            .type_mismatches()
+            .unwrap()
+            .iter()
+            .map(|m| {
+                (
+                    m.path().file_name().unwrap().to_owned(),
+                    m.extension().to_string(),
+                    m.content_type(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            mismatches,
+            vec![
+                (
+                    "corrupt.png".into(),
+                    "png".to_string(),
+                    ContentType::Unknown
+                ),
+                ("fake.PNG".into(), "PNG".to_string(), ContentType::Text),
+                ("renamed.txt".into(), "txt".to_string(), ContentType::Png),
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_extensions() {
+        let dir = TempDir::new("normalize");
+        for name in [
+            "a.JPG",
+            "b.jpg",
+            "c.jpeg",
+            "d.htm",
+            "backup.tar.gz",
+            "Old.TAR.GZ",
+            "plain.gz",
+            "types.d.ts",
+            "notes.markdown",
+            ".tar.gz",
+        ] {
+            fs::write(dir.join(name), "a").unwrap();
+        }
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let groups = |args: &Args| {
+            let res = analyze(args).unwrap();
+            let mut groups: Vec<_> = res
+                .file_info()
+                .unwrap()
+                .iter()
+                .map(|(ext, info)| (ext.clone(), info.num_files()))
+                .collect();
+            groups.sort();
+            groups
+        };
+        let raw = groups(&test_args);
+        test_args.set_normalize_extensions(vec![ExtensionNormalization::Case]);
+        let case_only = groups(&test_args);
+        test_args.set_normalize_extensions(vec![
+            ExtensionNormalization::Case,
+            ExtensionNormalization::Aliases,
+            ExtensionNormalization::Compound,
+        ]);
+        test_args.set_extension_aliases(vec!["markdown=txt".parse().unwrap()]);
+        let all = groups(&test_args);
+
+        let expected = |groups: &[(&str, u32)]| -> Vec<(String, u32)> {
+            groups.iter().map(|(e, n)| (e.to_string(), *n)).collect()
+        };
+        assert_eq!(
+            raw,
+            expected(&[
+                ("GZ", 1),
+                ("JPG", 1),
+                ("gz", 3),
+                ("htm", 1),
+                ("jpeg", 1),
+                ("jpg", 1),
+                ("markdown", 1),
+                ("ts", 1)
+            ])
+        );
+        assert_eq!(
+            case_only,
+            expected(&[
+                ("gz", 4),
+                ("htm", 1),
+                ("jpeg", 1),
+                ("jpg", 2),
+                ("markdown", 1),
+                ("ts", 1)
+            ])
+        );
+        assert_eq!(
+            all,
+            expected(&[
+                ("d.ts", 1),
+                ("gz", 2),
+                ("html", 1),
+                ("jpg", 3),
+                ("tar.gz", 2),
+                ("txt", 1)
+            ])
+        );
+
+        assert!("jpeg".parse::<ExtensionAlias>().is_err());
+        assert!("=jpg".parse::<ExtensionAlias>().is_err());
+        assert!(".jpeg=.jpg".parse::<ExtensionAlias>().is_ok());
+    }
+
+    #[test]
+    fn categories() {
+        let dir = TempDir::new("categories");
+        fs::create_dir_all(dir.join("tree")).unwrap();
+        for (name, size) in [
+            ("a.mp4", 100),
+            ("b.MKV", 50),
+            ("main.rs", 10),
+            ("backup.tar.gz", 20),
+            ("mystery.xyz", 20),
+        ] {
+            fs::write(dir.join("tree").join(name), "a".repeat(size)).unwrap();
+        }
+        let category_file = dir.join("categories.toml");
+
+        let mut test_args = Args::new(
+            dir.join("tree"),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_categories(true);
+        test_args.set_normalize_extensions(vec![ExtensionNormalization::Compound]);
+        let categories = |args: &Args| {
+            let res = analyze(args).unwrap();
+            assert!(res.file_info().is_none());
+            res.categories().unwrap().categories().to_vec()
+        };
+        let totals = |args: &Args| {
+            categories(args)
+                .iter()
+                .map(|c| (c.name().to_string(), c.totals()))
+                .collect::<Vec<_>>()
+        };
+        let builtin = categories(&test_args);
+
+        fs::write(
+            &category_file,
+            "[categories]\nmedia = [\".MP4\", \"mkv\"]\n",
+        )
+        .unwrap();
+        test_args.set_category_file(Some(category_file.clone()));
+        let added = totals(&test_args);
+        fs::write(
+            &category_file,
+            "replace_builtin = true\n[categories]\ncode = [\"rs\"]\n",
+        )
+        .unwrap();
+        let replaced = totals(&test_args);
+        fs::write(&category_file, "[categories]\nmedia = \"mp4\"\n").unwrap();
+        let invalid = analyze(&test_args);
+
+        let expected = |totals: &[(&str, u64, u64)]| -> Vec<(String, Totals)> {
+            totals
+                .iter()
+                .map(|(name, files, bytes)| (name.to_string(), Totals::new(*files, *bytes)))
+                .collect()
+        };
+        assert_eq!(
+            builtin
+                .iter()
+                .map(|c| (c.name().to_string(), c.totals()))
+                .collect::<Vec<_>>(),
+            expected(&[
+                ("video", 2, 150),
+                ("archive", 1, 20),
+                ("other", 1, 20),
+                ("source code", 1, 10)
+            ])
+        );
+        assert_eq!(builtin[0].percent_of_total_files(), 0.4);
+        assert_eq!(builtin[0].percent_of_total_size(), 0.75);
+        assert_eq!(
+            added,
+            expected(&[
+                ("media", 2, 150),
+                ("archive", 1, 20),
+                ("other", 1, 20),
+                ("source code", 1, 10)
+            ])
+        );
+        assert_eq!(replaced, expected(&[("other", 4, 190), ("code", 1, 10)]));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn categories_with_detect_type() {
+        let dir = TempDir::new("categories_detect_type");
+        for (name, contents) in [
+            ("main.rs", "fn main() {}".as_bytes()),
+            ("notes.txt", b"hello"),
+            ("prog", b"\x7fELF\x02\x01\x01\0\0"),
+            ("mystery.xyz", b"plain text"),
+            ("blob.xyz", b"\0\x01\x02"),
+        ] {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+
+        let mut test_args = Args::new(
+            dir.to_path_buf(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_categories(true);
+        test_args.set_detect_type(true);
+        let res = analyze(&test_args).unwrap();
+
+        //Text files are categorized by their extension, the detected type is only used when the
+        //extension is not in a category
+        let totals: Vec<_> = res
+            .categories()
+            .unwrap()
+            .categories()
+            .iter()
+            .map(|c| (c.name().to_string(), c.totals()))
+            .collect();
+        assert_eq!(
+            totals,
+            [
+                ("document".to_string(), Totals::new(2, 15)),
+                ("source code".to_string(), Totals::new(1, 12)),
+                ("executable".to_string(), Totals::new(1, 9)),
+                ("other".to_string(), Totals::new(1, 3)),
+            ]
+        );
+    }
 }
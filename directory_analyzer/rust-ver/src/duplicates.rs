@@ -0,0 +1,250 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use file_id::FileId;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{DuplicateInfo, DuplicateSet, EntryError};
+
+///How much of the start of a file is hashed before hashing the whole file
+const PARTIAL_HASH_BYTES: u64 = 4096;
+
+///Finds the duplicates among the candidates gathered in `info` while walking.
+///
+///Files are grouped by size, then by a hash of the start of the file and then by a hash of the
+///whole file, so only files that could be duplicates are ever fully read. Files with the same
+///hash are finally compared byte for byte since a hash match alone does not prove they are the
+///same. Paths that are hard links to the same file are reported separately since they take up
+///no extra space.
+///
+///Files that can't be read are left out and returned as errors, unless `strict` in which case
+///the first error is returned
+pub(crate) fn find_duplicates(info: &mut DuplicateInfo, strict: bool) -> Result<Vec<EntryError>> {
+    let mut errors = Errors {
+        strict,
+        errors: Vec::new(),
+    };
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for (path, size) in info.take_candidates() {
+        //Empty files are all the same but cost nothing to keep
+        if size > 0 {
+            by_size.entry(size).or_default().push(path);
+        }
+    }
+
+    let mut duplicate_sets = Vec::new();
+    let mut hard_link_sets = Vec::new();
+    for (size, paths) in by_size {
+        if paths.len() < 2 {
+            continue;
+        }
+        let mut by_id: HashMap<FileId, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            if let Some(id) = errors.check(&path, file_id::get_file_id(&path))? {
+                by_id.entry(id).or_default().push(path);
+            }
+        }
+        //Only one path of each hard linked file needs to be read
+        let mut files = Vec::new();
+        for mut links in by_id.into_values() {
+            links.sort();
+            files.push(links[0].clone());
+            if links.len() > 1 {
+                hard_link_sets.push(DuplicateSet::new(size, 0, links));
+            }
+        }
+        if files.len() < 2 {
+            continue;
+        }
+        for group in group_by_hash(files, partial_hash, &mut errors)? {
+            let groups = if size <= PARTIAL_HASH_BYTES {
+                //The whole file was already hashed
+                vec![group]
+            } else {
+                group_by_hash(group, full_hash, &mut errors)?
+            };
+            for group in groups {
+                for group in split_identical(group, &mut errors)? {
+                    let wasted_bytes = size * (group.len() as u64 - 1);
+                    duplicate_sets.push(DuplicateSet::new(size, wasted_bytes, group));
+                }
+            }
+        }
+    }
+    info.set_results(duplicate_sets, hard_link_sets);
+    Ok(errors.errors)
+}
+
+///Files that could not be read while looking for duplicates
+struct Errors {
+    ///Return the first error instead of keeping it
+    strict: bool,
+    errors: Vec<EntryError>,
+}
+
+impl Errors {
+    ///The value in `res`, or `None` if it is an error that was kept
+    fn check<T>(&mut self, path: &Path, res: io::Result<T>) -> Result<Option<T>> {
+        match res {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if self.strict => Err(e.into()),
+            Err(e) => {
+                self.errors
+                    .push(EntryError::from_error(path.to_path_buf(), &e.into()));
+                Ok(None)
+            }
+        }
+    }
+}
+
+///Groups `paths` by `hash`, leaving out any that don't share a hash with another path
+fn group_by_hash(
+    paths: Vec<PathBuf>,
+    hash: fn(&Path) -> io::Result<u128>,
+    errors: &mut Errors,
+) -> Result<Vec<Vec<PathBuf>>> {
+    let mut groups: HashMap<u128, Vec<PathBuf>> = HashMap::new();
+    for path in paths {
+        if let Some(hash) = errors.check(&path, hash(&path))? {
+            groups.entry(hash).or_default().push(path);
+        }
+    }
+    Ok(groups.into_values().filter(|g| g.len() > 1).collect())
+}
+
+///Splits `paths` into sets of files with the same contents, leaving out any file that is not the
+///same as another one
+fn split_identical(mut paths: Vec<PathBuf>, errors: &mut Errors) -> Result<Vec<Vec<PathBuf>>> {
+    let mut sets = Vec::new();
+    while paths.len() > 1 {
+        let reference = paths.remove(0);
+        let mut same = Vec::new();
+        let mut different = Vec::new();
+        let mut reference_readable = true;
+        for path in paths {
+            if !reference_readable {
+                different.push(path);
+                continue;
+            }
+            match same_contents(&reference, &path) {
+                Ok(true) => same.push(path),
+                Ok(false) => different.push(path),
+                Err((failed, e)) => {
+                    if failed == reference {
+                        errors.check::<()>(&reference, Err(e))?;
+                        reference_readable = false;
+                        different.push(path);
+                    } else {
+                        errors.check::<()>(&path, Err(e))?;
+                    }
+                }
+            }
+        }
+        if !reference_readable {
+            //Compare the rest among themselves
+            different.extend(same);
+        } else if !same.is_empty() {
+            same.insert(0, reference);
+            sets.push(same);
+        }
+        paths = different;
+    }
+    Ok(sets)
+}
+
+///If the files at `a` and `b` have the same contents, errors come with the path of the file
+///that could not be read
+fn same_contents<'a>(a: &'a Path, b: &'a Path) -> Result<bool, (&'a Path, io::Error)> {
+    let mut a_file = File::open(a).map_err(|e| (a, e))?;
+    let mut b_file = File::open(b).map_err(|e| (b, e))?;
+    let mut a_buf = vec![0; 64 * 1024];
+    let mut b_buf = vec![0; 64 * 1024];
+    loop {
+        let a_read = read_chunk(&mut a_file, &mut a_buf).map_err(|e| (a, e))?;
+        let b_read = read_chunk(&mut b_file, &mut b_buf).map_err(|e| (b, e))?;
+        if a_buf[..a_read] != b_buf[..b_read] {
+            return Ok(false);
+        }
+        if a_read == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+///Fills `buf` from `reader`, only reading less at the end of it
+fn read_chunk(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(filled)
+}
+
+fn partial_hash(path: &Path) -> io::Result<u128> {
+    hash_reader(File::open(path)?.take(PARTIAL_HASH_BYTES))
+}
+
+fn full_hash(path: &Path) -> io::Result<u128> {
+    hash_reader(File::open(path)?)
+}
+
+fn hash_reader(mut reader: impl Read) -> io::Result<u128> {
+    let mut hasher = Xxh3::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hasher.digest128())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use super::*;
+
+    #[test]
+    fn split_identical_compares_contents() {
+        let dir = PathBuf::from_str("../test/").unwrap();
+        let paths: Vec<_> = [
+            "file1.txt",
+            "file2.txt",
+            "folder1/file5.txt",
+            "folder2/file6.txt",
+            "folder2/folder3/folder4/deepfile1.txt",
+            "missing.txt",
+        ]
+        .iter()
+        .map(|name| dir.join(name))
+        .collect();
+        let mut errors = Errors {
+            strict: false,
+            errors: Vec::new(),
+        };
+
+        //file1.txt, file5.txt and deepfile1.txt are the same, the others are all different
+        let sets = split_identical(paths, &mut errors).unwrap();
+        assert_eq!(
+            sets,
+            [vec![
+                dir.join("file1.txt"),
+                dir.join("folder1/file5.txt"),
+                dir.join("folder2/folder3/folder4/deepfile1.txt"),
+            ]]
+        );
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].path(), dir.join("missing.txt"));
+    }
+}
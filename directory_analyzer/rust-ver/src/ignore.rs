@@ -0,0 +1,264 @@
+use std::{
+    path::{self, Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+use regex::RegexSet;
+
+use crate::Args;
+
+///Decides which entries are left out of the analysis.
+///
+///Everything is compiled once up front so checking an entry is cheap.
+#[derive(Debug)]
+pub(crate) struct IgnoreMatcher {
+    ///Path to analyze as given, entries are found below it
+    root: PathBuf,
+    ///Absolute version of `root`, used to compare against `paths`
+    abs_root: PathBuf,
+    ///Exact paths from --ignore-entries, made absolute
+    paths: Vec<PathBuf>,
+    ///Globs that match files and directories
+    globs: GlobSet,
+    ///Globs ending in `/` that only match directories
+    dir_globs: GlobSet,
+    regexes: RegexSet,
+}
+
+impl IgnoreMatcher {
+    ///Builds the matcher from `args`, `None` if nothing should be ignored
+    pub fn new(args: &Args) -> Result<Option<Self>> {
+        if !has_ignore_options(args) {
+            return Ok(None);
+        }
+        let paths = match set_up_ignore_these(args)? {
+            Some(paths) => paths.iter().map(path::absolute).collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let mut globs = GlobSetBuilder::new();
+        let mut dir_globs = GlobSetBuilder::new();
+        for pattern in args.ignore_globs() {
+            let (pattern, dir_only) = match pattern.strip_suffix('/') {
+                Some(p) => (p, true),
+                None => (pattern.as_str(), false),
+            };
+            //Like .gitignore, a pattern without a `/` matches at any depth and a
+            //leading `/` anchors it to the path being analyzed
+            let pattern = match pattern.strip_prefix('/') {
+                Some(p) => p.to_string(),
+                None if !pattern.contains('/') => format!("**/{pattern}"),
+                None => pattern.to_string(),
+            };
+            let glob = GlobBuilder::new(&pattern).literal_separator(true).build()?;
+            if dir_only {
+                dir_globs.add(glob);
+            } else {
+                globs.add(glob);
+            }
+        }
+        Ok(Some(Self {
+            root: args.path_to_analyze().clone(),
+            abs_root: path::absolute(args.path_to_analyze())?,
+            paths,
+            globs: globs.build()?,
+            dir_globs: dir_globs.build()?,
+            regexes: RegexSet::new(args.ignore_regexes())?,
+        }))
+    }
+
+    ///If the entry at `path`, which must be below the path being analyzed, should be left out
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        if !self.paths.is_empty() {
+            let abs_path = self.abs_root.join(relative);
+            if self.paths.contains(&abs_path) {
+                return true;
+            }
+        }
+        if self.globs.is_match(relative) || (is_dir && self.dir_globs.is_match(relative)) {
+            return true;
+        }
+        if !self.regexes.is_empty() {
+            return self.regexes.is_match(&to_slash_path(relative));
+        }
+        false
+    }
+}
+
+///If any of the options that leave entries out of the analysis are set
+pub(crate) fn has_ignore_options(args: &Args) -> bool {
+    args.ignore_entries().is_some()
+        || !args.ignore_globs().is_empty()
+        || !args.ignore_regexes().is_empty()
+        || args.respect_gitignore()
+}
+
+///Names of the files in each directory that ignore rules are read from, later files take precedence
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+///The ignore rules that apply to a directory, from the directory itself up to the root of the
+///repository the path being analyzed is in.
+///
+///Each directory only loads its own ignore files and shares the rules of its parents, so the
+///stack can be handed to other threads along with the directory.
+#[derive(Debug)]
+pub(crate) struct GitignoreStack {
+    rules: Gitignore,
+    ///Only for the rules of directories above the path being analyzed, which are built from
+    ///absolute paths. The path being analyzed as given and its absolute version, used to make the
+    ///paths being matched absolute too
+    above_root: Option<(PathBuf, PathBuf)>,
+    parent: Option<Arc<GitignoreStack>>,
+}
+
+impl GitignoreStack {
+    ///The rules that apply to `root` from above it, like git does when run in a subdirectory: the
+    ///repository's .git/info/exclude and the ignore files of every directory from the root of the
+    ///repository down to the parent of `root`.
+    ///
+    ///`None` if `root` is not inside a repository or is the root of one, its own files are loaded
+    ///by `for_dir`
+    pub fn for_root(root: &Path) -> Result<Option<Arc<GitignoreStack>>> {
+        let abs_root = path::absolute(root)?;
+        if abs_root.join(".git").exists() {
+            return Ok(None);
+        }
+        let Some(repo) = abs_root
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(".git").exists())
+        else {
+            return Ok(None);
+        };
+        let above_root = Some((root.to_path_buf(), abs_root.clone()));
+        let mut stack = None;
+        let exclude = repo.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            stack = Self::push(stack, repo, &[exclude], above_root.clone());
+        }
+        let dirs: Vec<_> = abs_root
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(repo))
+            .collect();
+        //From the root of the repository down so deeper files take precedence
+        for dir in dirs.into_iter().rev() {
+            let files: Vec<_> = IGNORE_FILES
+                .iter()
+                .map(|f| dir.join(f))
+                .filter(|f| f.is_file())
+                .collect();
+            if !files.is_empty() {
+                stack = Self::push(stack, dir, &files, above_root.clone());
+            }
+        }
+        Ok(stack)
+    }
+
+    ///The rules that apply to the entries of `dir`, given the rules that apply to `dir` itself.
+    ///
+    ///Returns `parent` unchanged if `dir` has no ignore files
+    pub fn for_dir(parent: Option<Arc<GitignoreStack>>, dir: &Path) -> Option<Arc<GitignoreStack>> {
+        let mut stack = parent;
+        //Repository wide excludes have a lower precedence than any .gitignore in the repository
+        let exclude = dir.join(".git").join("info").join("exclude");
+        if exclude.is_file() {
+            stack = Self::push(stack, dir, &[exclude], None);
+        }
+        let files: Vec<_> = IGNORE_FILES
+            .iter()
+            .map(|f| dir.join(f))
+            .filter(|f| f.is_file())
+            .collect();
+        if !files.is_empty() {
+            stack = Self::push(stack, dir, &files, None);
+        }
+        stack
+    }
+
+    fn push(
+        parent: Option<Arc<GitignoreStack>>,
+        dir: &Path,
+        files: &[PathBuf],
+        above_root: Option<(PathBuf, PathBuf)>,
+    ) -> Option<Arc<GitignoreStack>> {
+        let mut builder = GitignoreBuilder::new(dir);
+        for file in files {
+            if let Some(e) = builder.add(file) {
+                eprintln!(
+                    "WARNING: Problem reading \"{}\": {e}",
+                    file.to_string_lossy()
+                );
+            }
+        }
+        match builder.build() {
+            Ok(rules) => Some(Arc::new(GitignoreStack {
+                rules,
+                above_root,
+                parent,
+            })),
+            Err(e) => {
+                eprintln!(
+                    "WARNING: Ignoring rules in \"{}\": {e}",
+                    dir.to_string_lossy()
+                );
+                parent
+            }
+        }
+    }
+
+    ///If the entry at `path` is ignored, the deepest rule that matches it decides so a
+    ///negated rule can re-include something ignored further up
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut cur = Some(self);
+        while let Some(stack) = cur {
+            let matched = match &stack.above_root {
+                Some((root, abs_root)) => {
+                    let relative = path.strip_prefix(root).unwrap_or(path);
+                    stack.rules.matched(abs_root.join(relative), is_dir)
+                }
+                None => stack.rules.matched(path, is_dir),
+            };
+            match matched {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => cur = stack.parent.as_deref(),
+            }
+        }
+        false
+    }
+}
+
+///`path` with `/` as the separator on every platform so regexes behave the same everywhere
+fn to_slash_path(path: &Path) -> String {
+    let components: Vec<_> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    components.join("/")
+}
+
+fn set_up_ignore_these(args: &Args) -> Result<Option<Vec<PathBuf>>, anyhow::Error> {
+    let ignore_these: Option<Vec<_>> = if let Some(s) = args.ignore_entries() {
+        let paths: Vec<_> = s.split(',').map(|s| s.trim()).map(PathBuf::from).collect();
+        for p in &paths {
+            let exists = p.try_exists()?;
+            if !exists {
+                eprintln!(
+                    "WARNING: Can't ignore \"{}\" because it doesn't exist",
+                    p.to_string_lossy()
+                );
+            }
+        }
+        Some(paths)
+    } else {
+        None
+    };
+    Ok(ignore_these)
+}
@@ -1,5 +1,8 @@
 mod analyze;
+mod duplicates;
+mod ignore;
 mod output;
+mod tables;
 mod types;
 
 pub use analyze::*;
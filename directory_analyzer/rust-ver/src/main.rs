@@ -1,10 +1,17 @@
 use std::time::Instant;
 
 use clap::Parser;
-use dira::{analyze, output, Args};
+use dira::{analyze, diff_output, output, Args, Command};
 
 fn main() {
     let args = Args::parse();
+    if let Some(Command::Diff(diff_args)) = args.command() {
+        match diff_output(diff_args) {
+            Ok(out) => println!("{out}"),
+            Err(e) => eprintln!("{e:#}"),
+        }
+        return;
+    }
     let start_time = match args.time() {
         true => Some(Instant::now()),
         false => None,
@@ -13,7 +20,7 @@ fn main() {
     let out = output(&args, analyzed_info);
     match out {
         Ok(out) => println!("{out}"),
-        Err(e) => eprintln!("{e}"),
+        Err(e) => eprintln!("{e:#}"),
     }
     if let Some(start_time) = start_time {
         let end_time = Instant::now();
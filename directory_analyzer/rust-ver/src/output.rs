@@ -1,26 +1,105 @@
-use std::path::Path;
+use std::{fs, path::Path};
 
 use anyhow::Error;
-use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use rust_xlsxwriter::{Chart, ChartType, Format, Workbook, Worksheet};
+use serde::Serialize;
 
-use crate::{AnalyzedInfo, Args};
+use crate::{
+    tables::{diff_tables, tables, Cell, Table, SIZE_TABLE_NAME},
+    AnalyzedInfo, Args, DiffArgs, DisplaySizes, OutputFormat, SizeUnits, Snapshot, SnapshotDiff,
+};
+
+///Version of the JSON output schema.
+///
+///Bumped whenever a field is renamed, removed or changes meaning. New fields can be added without a bump.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+///Top level object of the JSON output
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    info: &'a AnalyzedInfo,
+}
 
 ///Returns a string analyzed_info based on how args is configured
 pub fn output(args: &Args, analyzed_info: Result<AnalyzedInfo, Error>) -> anyhow::Result<String> {
     match analyzed_info {
-        Ok(info) => match args.export_xlsx() {
-            Some(path) => {
+        Ok(info) => {
+            let mut saved_to = Vec::new();
+            if let Some(path) = args.export_xlsx() {
                 let workbook = create_workbook(&info)?;
                 let path = write_workbook(path, workbook)?;
-                let out = format!("Info saved to {}", &path.to_string_lossy());
-                Ok(out)
+                saved_to.push(format!("Info saved to {}", &path.to_string_lossy()));
+            }
+            if let Some(dir) = args.export_csv() {
+                write_csv(dir, &info)?;
+                saved_to.push(format!("CSV files saved to {}", &dir.to_string_lossy()));
+            }
+            if let Some(path) = args.save_snapshot() {
+                Snapshot::new(args.path_to_analyze(), &info)?.save(path)?;
+                saved_to.push(format!("Snapshot saved to {}", &path.to_string_lossy()));
             }
-            None => Ok(format!("{info}")),
-        },
+            let formatted = match args.output_format() {
+                OutputFormat::Text => format!("{info}"),
+                OutputFormat::Json => to_json(&info)?,
+            };
+            match args.output_file() {
+                Some(path) => {
+                    fs::write(path, formatted)?;
+                    saved_to.push(format!("Info saved to {}", &path.to_string_lossy()));
+                }
+                //Keep stdout clean for anything that isn't the default text output
+                None if saved_to.is_empty() || args.output_format() != OutputFormat::Text => {
+                    return Ok(formatted)
+                }
+                None => {}
+            }
+            Ok(saved_to.join("\n"))
+        }
         Err(e) => Err(e),
     }
 }
 
+///Compares the snapshots given in `args` and returns the differences based on how args is configured
+pub fn diff_output(args: &DiffArgs) -> anyhow::Result<String> {
+    let old = Snapshot::load(args.old_snapshot())?;
+    let new = Snapshot::load(args.new_snapshot())?;
+    let diff = SnapshotDiff::new(&old, &new);
+    let mut saved_to = Vec::new();
+    if let Some(path) = args.export_xlsx() {
+        let mut workbook = Workbook::new();
+        push_tables(&mut workbook, diff_tables(&diff), args.human_readable())?;
+        let path = write_workbook(path, workbook)?;
+        saved_to.push(format!("Differences saved to {}", &path.to_string_lossy()));
+    }
+    let formatted = match args.output_format() {
+        OutputFormat::Text => format!("{}", diff.with_units(args.human_readable())),
+        OutputFormat::Json => serde_json::to_string_pretty(&diff)?,
+    };
+    match args.output_file() {
+        Some(path) => {
+            fs::write(path, formatted)?;
+            saved_to.push(format!("Differences saved to {}", &path.to_string_lossy()));
+        }
+        //Keep stdout clean for anything that isn't the default text output
+        None if saved_to.is_empty() || args.output_format() != OutputFormat::Text => {
+            return Ok(formatted)
+        }
+        None => {}
+    }
+    Ok(saved_to.join("\n"))
+}
+
+///Serializes `info` to pretty printed JSON with the schema version added
+pub fn to_json(info: &AnalyzedInfo) -> anyhow::Result<String> {
+    let report = JsonReport {
+        schema_version: JSON_SCHEMA_VERSION,
+        info,
+    };
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
 fn write_workbook(path: &Path, mut workbook: Workbook) -> Result<std::path::PathBuf, Error> {
     let mut path = path.to_path_buf();
     if path.extension().is_none() {
@@ -32,106 +111,140 @@ fn write_workbook(path: &Path, mut workbook: Workbook) -> Result<std::path::Path
 
 fn create_workbook(info: &AnalyzedInfo) -> Result<Workbook, Error> {
     let mut workbook = Workbook::new();
+    push_tables(&mut workbook, tables(info), info.size_units())?;
+    if let Some(sizes) = info.size_histogram() {
+        workbook.push_worksheet(size_chart_worksheet(sizes.buckets().len() as u32)?);
+    }
+    Ok(workbook)
+}
+
+///Adds one worksheet per table, sizes are formatted to be readable if there are `size_units`
+fn push_tables(
+    workbook: &mut Workbook,
+    tables: Vec<Table>,
+    size_units: Option<SizeUnits>,
+) -> Result<(), Error> {
     let bold_format = Format::new().set_bold();
+    //Sizes stay in bytes so they can still be used in formulas, only their number format changes.
+    //Number formats can only scale by powers of 1000 so kB/MB/GB are used for iec too
+    let size_format =
+        Format::new().set_num_format(r#"[<1000000]0.0," kB";[<1000000000]0.0,," MB";0.0,,," GB""#);
+    for table in tables {
+        let mut worksheet = Worksheet::new();
+        worksheet.set_name(table.name)?;
+        for (col, header) in table.headers.iter().enumerate() {
+            worksheet.write_with_format(0, col as u16, *header, &bold_format)?;
+        }
+        for (i, row) in table.rows.iter().enumerate() {
+            let i = i as u32 + 1;
+            for (col, cell) in row.iter().enumerate() {
+                let col = col as u16;
+                match cell {
+                    Cell::Text(s) => worksheet.write(i, col, s)?,
+                    Cell::Number(n) => worksheet.write(i, col, *n)?,
+                    Cell::Float(n) => worksheet.write(i, col, *n)?,
+                    Cell::Signed(n) => worksheet.write(i, col, *n)?,
+                    Cell::Bytes(n) => match size_units {
+                        Some(_) => worksheet.write_with_format(i, col, *n, &size_format)?,
+                        None => worksheet.write(i, col, *n)?,
+                    },
+                };
+            }
+        }
+        worksheet.autofit();
+        workbook.push_worksheet(worksheet);
+    }
+    Ok(())
+}
+
+///A worksheet holding only a column chart of the "All files" rows of the size distribution table
+fn size_chart_worksheet(buckets: u32) -> Result<Worksheet, Error> {
+    let mut chart = Chart::new(ChartType::Column);
+    chart.title().set_name("Files by size");
+    chart.x_axis().set_name("Size range");
+    chart.y_axis().set_name("Num files");
+    chart.legend().set_hidden();
+    chart
+        .add_series()
+        .set_name("Num files")
+        .set_categories((SIZE_TABLE_NAME, 1, 1, buckets, 1))
+        .set_values((SIZE_TABLE_NAME, 1, 2, buckets, 2));
     let mut worksheet = Worksheet::new();
-    worksheet.set_name("General Info")?;
-    let mut cur_col = 0;
-    worksheet.write_with_format(0, cur_col, "Found dirs", &bold_format)?;
-    worksheet.write(1, cur_col, info.found_dirs())?;
-    cur_col += 1;
-    worksheet.write_with_format(0, cur_col, "Found files", &bold_format)?;
-    worksheet.write(1, cur_col, info.found_files())?;
-    cur_col += 1;
-    worksheet.write_with_format(0, cur_col, "Total bytes", &bold_format)?;
-    worksheet.write(1, cur_col, info.total_bytes())?;
-    cur_col += 1;
-    if let Some(symlink_info) = info.found_symlinks() {
-        worksheet.write_with_format(0, cur_col, "Found symlinks", &bold_format)?;
-        worksheet.write(1, cur_col, symlink_info.found_symlinks())?;
-        cur_col += 1;
-        worksheet.write_with_format(0, cur_col, "File symlinks", &bold_format)?;
-        worksheet.write(1, cur_col, symlink_info.file_symlinks())?;
-        cur_col += 1;
-        worksheet.write_with_format(0, cur_col, "Dir symlinks", &bold_format)?;
-        worksheet.write(1, cur_col, symlink_info.dir_symlinks())?;
-        // cur_col += 1;
+    worksheet.set_name("Size Chart")?;
+    worksheet.insert_chart(0, 0, &chart)?;
+    Ok(worksheet)
+}
+
+///Writes one CSV file per table into `dir`, creating it if needed
+fn write_csv(dir: &Path, info: &AnalyzedInfo) -> Result<(), Error> {
+    fs::create_dir_all(dir)?;
+    for table in tables(info) {
+        fs::write(dir.join(table.csv_file_name()), table.to_csv())?;
     }
-    worksheet.autofit();
-    workbook.push_worksheet(worksheet);
-    if let Some(file_info) = info.file_info() {
-        let mut fi_worksheet = Worksheet::new();
-        let mut cur_col = 0;
-        fi_worksheet.set_name("File Info")?;
-        let down_offset = 0;
-        let tmp: Vec<_> = file_info.iter().enumerate().collect();
-        fi_worksheet.write_with_format(down_offset, cur_col, "File type", &bold_format)?;
-        cur_col += 1;
-        fi_worksheet.write_with_format(down_offset, cur_col, "Num files", &bold_format)?;
-        cur_col += 1;
-        fi_worksheet.write_with_format(down_offset, cur_col, "% of total files", &bold_format)?;
-        cur_col += 1;
-        fi_worksheet.write_with_format(
-            down_offset,
-            cur_col,
-            "Total size of files(bytes)",
-            &bold_format,
-        )?;
-        cur_col += 1;
-
-        fi_worksheet.write_with_format(down_offset, cur_col, "% of total bytes", &bold_format)?;
-        cur_col += 1;
-
-        fi_worksheet.write_with_format(down_offset, cur_col, "Largest file", &bold_format)?;
-        cur_col += 1;
-        fi_worksheet.write_with_format(
-            down_offset,
-            cur_col,
-            "Largest file size(bytes)",
-            &bold_format,
-        )?;
-        cur_col += 1;
-        fi_worksheet.write_with_format(down_offset, cur_col, "Smallest file", &bold_format)?;
-        cur_col += 1;
-        fi_worksheet.write_with_format(
-            down_offset,
-            cur_col,
-            "Smallest file size(bytes)",
-            &bold_format,
-        )?;
-
-        for (i, (f_type, f_info)) in tmp {
-            let i = i as u32 + down_offset + 1;
-            let mut cur_col = 0;
-            //File type
-            fi_worksheet.write(i, cur_col, f_type)?;
-            cur_col += 1;
-            //Num Files
-            fi_worksheet.write(i, cur_col, f_info.num_files())?;
-            cur_col += 1;
-            //% of total files
-            let (per_tot_file, per_tot_size) = f_info.percentages_in_string();
-            fi_worksheet.write(i, cur_col, per_tot_file)?;
-            cur_col += 1;
-            //Total size of files
-            fi_worksheet.write(i, cur_col, f_info.size_in_bytes())?;
-            cur_col += 1;
-            //% of total size
-            fi_worksheet.write(i, cur_col, per_tot_size)?;
-            cur_col += 1;
-            //Largest file path
-            fi_worksheet.write(i, cur_col, f_info.largest_file().path().to_string_lossy())?;
-            cur_col += 1;
-            //Largest file size
-            fi_worksheet.write(i, cur_col, f_info.largest_file().size())?;
-            cur_col += 1;
-            //Smallest file path
-            fi_worksheet.write(i, cur_col, f_info.smallest_file().path().to_string_lossy())?;
-            cur_col += 1;
-            //Smallest file size
-            fi_worksheet.write(i, cur_col, f_info.smallest_file().size())?;
-        }
-        fi_worksheet.autofit();
-        workbook.push_worksheet(fi_worksheet);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use crate::analyze;
+
+    use super::*;
+
+    #[test]
+    fn json_output_has_schema_version_and_percentages() {
+        let test_args = Args::new(
+            PathBuf::from_str("../test/").unwrap(),
+            false,
+            true,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        let info = analyze(&test_args).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&to_json(&info).unwrap()).unwrap();
+        assert_eq!(json["schema_version"], JSON_SCHEMA_VERSION);
+        assert_eq!(json["found_files"], 7);
+        assert_eq!(json["total_bytes"], 432);
+        let txt = &json["file_info"]["txt"];
+        assert_eq!(txt["num_files"], 5);
+        assert_eq!(txt["largest_file"]["size"], 14);
+        assert!(txt["percent_of_total_files"].as_f64().unwrap() > 0.0);
+        assert!(txt["percent_of_total_size"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn xlsx_sizes_stay_in_bytes_with_iec() {
+        let mut test_args = Args::new(
+            PathBuf::from_str("../test/").unwrap(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            false,
+            None,
+            None,
+            false,
+            false,
+        );
+        test_args.set_human_readable(Some(SizeUnits::Iec));
+        let info = analyze(&test_args).unwrap();
+        let buf = create_workbook(&info).unwrap().save_to_buffer().unwrap();
+        let mut xlsx = zip::ZipArchive::new(std::io::Cursor::new(buf)).unwrap();
+        let mut sheet = String::new();
+        std::io::Read::read_to_string(
+            &mut xlsx.by_name("xl/worksheets/sheet1.xml").unwrap(),
+            &mut sheet,
+        )
+        .unwrap();
+        //The total size in General Info is still 432 bytes, not 0.4 KiB
+        assert!(sheet.contains("<v>432</v>"), "{sheet}");
     }
-    Ok(workbook)
 }
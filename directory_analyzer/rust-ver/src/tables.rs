@@ -0,0 +1,821 @@
+use crate::{
+    format_percent, format_timestamp, AgeHistogram, AgeTime, AnalyzedInfo, FileChange,
+    FileTimeRecord, OwnerTotals, SizeBasis, SizeHistogram, SnapshotDiff, TotalsChange,
+};
+
+///A single value in a `Table`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Cell {
+    Text(String),
+    Number(u64),
+    Float(f64),
+    ///A size in bytes, given a number format in the xlsx export with --human-readable
+    Bytes(u64),
+    ///A change in a count or size
+    Signed(i64),
+}
+
+impl Cell {
+    ///The value as it is written to a CSV file
+    pub fn to_csv_field(&self) -> String {
+        match self {
+            Cell::Text(s) => csv_quote(s),
+            Cell::Number(n) | Cell::Bytes(n) => n.to_string(),
+            Cell::Float(n) => n.to_string(),
+            Cell::Signed(n) => n.to_string(),
+        }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<u64> for Cell {
+    fn from(value: u64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(value: f64) -> Self {
+        Cell::Float(value)
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(value: u32) -> Self {
+        Cell::Number(value as u64)
+    }
+}
+
+///Tabular form of part of `AnalyzedInfo`.
+///
+///Used by both the xlsx and CSV exporters so they always have the same columns
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Table {
+    ///Name of the worksheet, also used to name the CSV file
+    pub name: &'static str,
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    ///File name used when exporting the table to CSV, e.g. "General Info" -> "general_info.csv"
+    pub fn csv_file_name(&self) -> String {
+        format!("{}.csv", self.name.to_lowercase().replace(' ', "_"))
+    }
+
+    ///The table as RFC 4180 CSV, including the header row
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        let header: Vec<_> = self.headers.iter().map(|h| csv_quote(h)).collect();
+        out.push_str(&header.join(","));
+        out.push_str("\r\n");
+        for row in &self.rows {
+            let row: Vec<_> = row.iter().map(Cell::to_csv_field).collect();
+            out.push_str(&row.join(","));
+            out.push_str("\r\n");
+        }
+        out
+    }
+}
+
+///Quotes a field if it contains a comma, quote or line break, doubling any quotes inside it
+fn csv_quote(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+///Name of the table made by `size_table`, used by the xlsx chart to refer to it
+pub(crate) const SIZE_TABLE_NAME: &str = "Size Distribution";
+
+///Builds all the tables for `info`, tables for options that were not enabled are left out
+pub(crate) fn tables(info: &AnalyzedInfo) -> Vec<Table> {
+    let mut out = vec![general_info_table(info)];
+    if let Some(table) = categories_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = file_info_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = dir_sizes_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = largest_files_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = age_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = size_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = owners_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = permission_audit_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = type_mismatches_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = duplicates_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = broken_symlinks_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = mount_points_table(info) {
+        out.push(table);
+    }
+    if let Some(table) = errors_table(info) {
+        out.push(table);
+    }
+    out
+}
+
+///Builds the tables of what changed between two snapshots
+pub(crate) fn diff_tables(diff: &SnapshotDiff) -> Vec<Table> {
+    let mut summary = Table {
+        name: "Diff Summary",
+        headers: vec![
+            "Old snapshot",
+            "Old snapshot time",
+            "New snapshot",
+            "New snapshot time",
+        ],
+        rows: vec![vec![
+            diff.old_path().into(),
+            format_timestamp(diff.old_created()).into(),
+            diff.new_path().into(),
+            format_timestamp(diff.new_created()).into(),
+        ]],
+    };
+    summary.headers.extend(TOTALS_CHANGE_HEADERS[1..].iter());
+    summary.rows[0].extend(totals_change_cells(diff.total()).into_iter().skip(1));
+    let mut out = vec![
+        summary,
+        totals_change_table("Extension Changes", "Extension", diff.extensions()),
+        totals_change_table("Dir Changes", "Directory", diff.dirs()),
+    ];
+    if let Some(files) = diff.files() {
+        let groups = [
+            ("Added", files.added()),
+            ("Removed", files.removed()),
+            ("Grown", files.grown()),
+        ];
+        let rows = groups
+            .into_iter()
+            .flat_map(|(change, files)| {
+                files
+                    .iter()
+                    .map(move |file| file_change_cells(change, file))
+            })
+            .collect();
+        out.push(Table {
+            name: "File Changes",
+            headers: vec![
+                "Change",
+                "Path",
+                "Old size(bytes)",
+                "New size(bytes)",
+                "Size change(bytes)",
+            ],
+            rows,
+        });
+    }
+    out
+}
+
+const TOTALS_CHANGE_HEADERS: [&str; 7] = [
+    "Name",
+    "Old num files",
+    "New num files",
+    "Num files change",
+    "Old size(bytes)",
+    "New size(bytes)",
+    "Size change(bytes)",
+];
+
+fn totals_change_cells(change: &TotalsChange) -> Vec<Cell> {
+    vec![
+        change.name().into(),
+        change.old().files().into(),
+        change.new_totals().files().into(),
+        Cell::Signed(change.files_change()),
+        Cell::Bytes(change.old().bytes()),
+        Cell::Bytes(change.new_totals().bytes()),
+        Cell::Signed(change.bytes_change()),
+    ]
+}
+
+fn totals_change_table(
+    name: &'static str,
+    name_header: &'static str,
+    changes: &[TotalsChange],
+) -> Table {
+    let mut headers = TOTALS_CHANGE_HEADERS.to_vec();
+    headers[0] = name_header;
+    Table {
+        name,
+        headers,
+        rows: changes.iter().map(totals_change_cells).collect(),
+    }
+}
+
+fn file_change_cells(change: &str, file: &FileChange) -> Vec<Cell> {
+    let size = |size: Option<u64>| match size {
+        Some(size) => Cell::Bytes(size),
+        None => "".into(),
+    };
+    vec![
+        change.into(),
+        file.path().into(),
+        size(file.old_size()),
+        size(file.new_size()),
+        Cell::Signed(file.size_change()),
+    ]
+}
+
+fn general_info_table(info: &AnalyzedInfo) -> Table {
+    let mut headers = vec!["Found dirs", "Found files", "Total bytes"];
+    let mut row: Vec<Cell> = vec![
+        info.found_dirs().into(),
+        info.found_files().into(),
+        Cell::Bytes(info.total_bytes()),
+    ];
+    if let Some(allocated) = info.allocated_bytes() {
+        headers.push("Allocated bytes");
+        row.push(Cell::Bytes(allocated));
+    }
+    headers.extend(["Hard links", "Bytes saved by hard links"]);
+    row.extend([
+        info.hard_links().into(),
+        Cell::Bytes(info.bytes_saved_by_hardlinks()),
+    ]);
+    if let Some(ignored) = info.ignored_entries() {
+        headers.push("Ignored entries");
+        row.push(ignored.into());
+    }
+    if let Some(special) = info.special_files() {
+        headers.extend(["FIFOs", "Sockets", "Block devices", "Character devices"]);
+        row.extend([
+            special.fifos().into(),
+            special.sockets().into(),
+            special.block_devices().into(),
+            special.character_devices().into(),
+        ]);
+    }
+    if let Some(sizes) = info.size_histogram() {
+        headers.extend(["Mean file size(bytes)", "Median file size(bytes)"]);
+        row.extend(mean_median_cells(sizes));
+    }
+    headers.push("Errors");
+    row.push((info.errors().len() as u64).into());
+    if let Some(mount_points) = info.skipped_mount_points() {
+        headers.push("Skipped mount points");
+        row.push((mount_points.len() as u64).into());
+    }
+    if let Some(symlink_info) = info.found_symlinks() {
+        headers.extend([
+            "Found symlinks",
+            "File symlinks",
+            "Dir symlinks",
+            "Special file symlinks",
+            "Broken symlinks",
+        ]);
+        row.extend([
+            symlink_info.found_symlinks().into(),
+            symlink_info.file_symlinks().into(),
+            symlink_info.dir_symlinks().into(),
+            symlink_info.special_symlinks().into(),
+            symlink_info.broken_symlinks().into(),
+        ]);
+    }
+    Table {
+        name: "General Info",
+        headers,
+        rows: vec![row],
+    }
+}
+
+///One row per category, most bytes first
+fn categories_table(info: &AnalyzedInfo) -> Option<Table> {
+    let rows = info
+        .categories()?
+        .categories()
+        .iter()
+        .map(|category| {
+            vec![
+                category.name().into(),
+                category.totals().files().into(),
+                format_percent(category.percent_of_total_files()).into(),
+                Cell::Bytes(category.totals().bytes()),
+                format_percent(category.percent_of_total_size()).into(),
+            ]
+        })
+        .collect();
+    Some(Table {
+        name: "Categories",
+        headers: vec![
+            "Category",
+            "Num files",
+            "% of total files",
+            "Total size of files(bytes)",
+            match info.size_basis() {
+                SizeBasis::Apparent => "% of total bytes",
+                SizeBasis::Allocated => "% of allocated bytes",
+            },
+        ],
+        rows,
+    })
+}
+
+fn file_info_table(info: &AnalyzedInfo) -> Option<Table> {
+    let file_info = info.sorted_file_info()?;
+    let allocated = info.allocated_bytes().is_some();
+    let mut headers = vec![
+        "File type",
+        "Num files",
+        "% of total files",
+        "Total size of files(bytes)",
+    ];
+    if allocated {
+        headers.push("Allocated size of files(bytes)");
+    }
+    headers.extend([
+        match info.size_basis() {
+            SizeBasis::Apparent => "% of total bytes",
+            SizeBasis::Allocated => "% of allocated bytes",
+        },
+        "Largest file",
+        "Largest file size(bytes)",
+        "Smallest file",
+        "Smallest file size(bytes)",
+    ]);
+    let age = info.age_histogram().is_some();
+    if age {
+        headers.extend([
+            "Oldest file",
+            "Oldest file time",
+            "Newest file",
+            "Newest file time",
+        ]);
+    }
+    let sizes = info.size_histogram().is_some();
+    if sizes {
+        headers.extend(["Mean file size(bytes)", "Median file size(bytes)"]);
+    }
+    let rows = file_info
+        .into_iter()
+        .map(|(f_type, f_info)| {
+            let (per_tot_file, per_tot_size) = f_info.percentages_in_string();
+            let mut row: Vec<Cell> = vec![
+                f_type.as_str().into(),
+                f_info.num_files().into(),
+                per_tot_file.into(),
+                Cell::Bytes(f_info.size_in_bytes()),
+            ];
+            if allocated {
+                row.push(Cell::Bytes(f_info.allocated_bytes().unwrap_or_default()));
+            }
+            row.extend([
+                per_tot_size.into(),
+                f_info
+                    .largest_file()
+                    .path()
+                    .to_string_lossy()
+                    .to_string()
+                    .into(),
+                Cell::Bytes(f_info.largest_file().size()),
+                f_info
+                    .smallest_file()
+                    .path()
+                    .to_string_lossy()
+                    .to_string()
+                    .into(),
+                Cell::Bytes(f_info.smallest_file().size()),
+            ]);
+            if age {
+                for record in [f_info.oldest_file(), f_info.newest_file()] {
+                    row.extend(time_record_cells(record));
+                }
+            }
+            if sizes {
+                match f_info.size_histogram() {
+                    Some(sizes) => row.extend(mean_median_cells(sizes)),
+                    None => row.extend(["".into(), "".into()]),
+                }
+            }
+            row
+        })
+        .collect();
+    Some(Table {
+        name: "File Info",
+        headers,
+        rows,
+    })
+}
+
+///Path and time of `record`, empty if there is none
+fn time_record_cells(record: Option<&FileTimeRecord>) -> [Cell; 2] {
+    match record {
+        Some(record) => [
+            record.path().to_string_lossy().to_string().into(),
+            format_timestamp(record.time()).into(),
+        ],
+        None => ["".into(), "".into()],
+    }
+}
+
+///Mean and median of `sizes`, empty if there were no files
+fn mean_median_cells(sizes: &SizeHistogram) -> [Cell; 2] {
+    match (sizes.mean(), sizes.median()) {
+        (Some(mean), Some(median)) => [mean.into(), median.into()],
+        _ => ["".into(), "".into()],
+    }
+}
+
+fn dir_sizes_table(info: &AnalyzedInfo) -> Option<Table> {
+    let dir_sizes = info.dir_sizes()?;
+    let headers = vec![
+        "Directory",
+        "Depth",
+        "Total size of files(bytes)",
+        "Num files",
+        "Num dirs",
+    ];
+    let rows = dir_sizes
+        .report()
+        .into_iter()
+        .map(|record| {
+            vec![
+                record.path.to_string_lossy().to_string().into(),
+                (record.depth as u64).into(),
+                Cell::Bytes(record.size.bytes()),
+                record.size.files().into(),
+                record.size.dirs().into(),
+            ]
+        })
+        .collect();
+    Some(Table {
+        name: "Dir Sizes",
+        headers,
+        rows,
+    })
+}
+
+///The largest files overall followed by the largest files of each type if file info was gathered
+fn largest_files_table(info: &AnalyzedInfo) -> Option<Table> {
+    let largest_files = info.largest_files()?;
+    let headers = vec!["File type", "Rank", "Path", "Size(bytes)"];
+    let mut groups = vec![("All files", largest_files)];
+    if let Some(file_info) = info.sorted_file_info() {
+        groups.extend(
+            file_info
+                .into_iter()
+                .filter_map(|(f_type, f_info)| Some((f_type.as_str(), f_info.largest_files()?))),
+        );
+    }
+    let rows = groups
+        .into_iter()
+        .flat_map(|(f_type, largest_files)| {
+            largest_files
+                .sorted()
+                .into_iter()
+                .enumerate()
+                .map(move |(i, record)| {
+                    vec![
+                        f_type.into(),
+                        (i as u64 + 1).into(),
+                        record.path().to_string_lossy().to_string().into(),
+                        Cell::Bytes(record.size()),
+                    ]
+                })
+        })
+        .collect();
+    Some(Table {
+        name: "Largest Files",
+        headers,
+        rows,
+    })
+}
+
+///The age ranges of all files followed by the age ranges of each type if file info was gathered
+fn age_table(info: &AnalyzedInfo) -> Option<Table> {
+    let histogram = info.age_histogram()?;
+    let mut groups: Vec<(&str, &AgeHistogram)> = vec![("All files", histogram)];
+    if let Some(file_info) = info.sorted_file_info() {
+        groups.extend(
+            file_info
+                .into_iter()
+                .filter_map(|(f_type, f_info)| Some((f_type.as_str(), f_info.age_histogram()?))),
+        );
+    }
+    let rows = groups
+        .into_iter()
+        .flat_map(|(f_type, histogram)| {
+            histogram.buckets().iter().map(move |bucket| {
+                vec![
+                    f_type.into(),
+                    bucket.label().into(),
+                    bucket.files().into(),
+                    Cell::Bytes(bucket.bytes()),
+                ]
+            })
+        })
+        .collect();
+    Some(Table {
+        name: "Age",
+        headers: vec![
+            match histogram.time() {
+                AgeTime::Modified => "File type(by modified time)",
+                AgeTime::Accessed => "File type(by accessed time)",
+                AgeTime::Changed => "File type(by changed time)",
+            },
+            "Age",
+            "Num files",
+            "Total size of files(bytes)",
+        ],
+        rows,
+    })
+}
+
+///The size ranges of all files followed by the size ranges of each type if file info was gathered.
+///
+///The "All files" rows come first so the xlsx chart can plot them
+fn size_table(info: &AnalyzedInfo) -> Option<Table> {
+    let histogram = info.size_histogram()?;
+    let mut groups: Vec<(&str, &SizeHistogram)> = vec![("All files", histogram)];
+    if let Some(file_info) = info.sorted_file_info() {
+        groups.extend(
+            file_info
+                .into_iter()
+                .filter_map(|(f_type, f_info)| Some((f_type.as_str(), f_info.size_histogram()?))),
+        );
+    }
+    let rows = groups
+        .into_iter()
+        .flat_map(|(f_type, histogram)| {
+            histogram.buckets().iter().map(move |bucket| {
+                vec![
+                    f_type.into(),
+                    bucket.label().into(),
+                    bucket.files().into(),
+                    Cell::Bytes(bucket.bytes()),
+                ]
+            })
+        })
+        .collect();
+    Some(Table {
+        name: SIZE_TABLE_NAME,
+        headers: vec![
+            "File type",
+            "Size range",
+            "Num files",
+            "Total size of files(bytes)",
+        ],
+        rows,
+    })
+}
+
+///Users followed by groups, each with the most bytes first
+fn owners_table(info: &AnalyzedInfo) -> Option<Table> {
+    let owners = info.owners()?;
+    let row = |kind: &str, owner: &OwnerTotals| -> Vec<Cell> {
+        vec![
+            kind.into(),
+            owner.id().into(),
+            owner.name().unwrap_or_default().into(),
+            if owner.orphaned() { "Yes" } else { "No" }.into(),
+            owner.totals().files().into(),
+            Cell::Bytes(owner.totals().bytes()),
+        ]
+    };
+    let rows = owners
+        .users()
+        .iter()
+        .map(|user| row("User", user))
+        .chain(owners.groups().iter().map(|group| row("Group", group)))
+        .collect();
+    Some(Table {
+        name: "Owners",
+        headers: vec![
+            "Kind",
+            "ID",
+            "Name",
+            "Orphaned",
+            "Num files",
+            "Total size of files(bytes)",
+        ],
+        rows,
+    })
+}
+
+///One row per finding, sorted by finding and then path
+fn permission_audit_table(info: &AnalyzedInfo) -> Option<Table> {
+    let rows = info
+        .permission_audit()?
+        .findings()
+        .iter()
+        .map(|finding| {
+            vec![
+                finding.issue().to_string().into(),
+                finding.path().to_string_lossy().to_string().into(),
+                format!("{:04o}", finding.mode()).into(),
+                finding.uid().into(),
+                match finding.parent_uid() {
+                    Some(uid) => uid.into(),
+                    None => "".into(),
+                },
+            ]
+        })
+        .collect();
+    Some(Table {
+        name: "Permission Audit",
+        headers: vec![
+            "Finding",
+            "Path",
+            "Mode",
+            "Owner uid",
+            "Directory owner uid",
+        ],
+        rows,
+    })
+}
+
+///Files whose extension does not match their content, sorted by path
+fn type_mismatches_table(info: &AnalyzedInfo) -> Option<Table> {
+    let rows = info
+        .type_mismatches()?
+        .iter()
+        .map(|mismatch| {
+            vec![
+                mismatch.path().to_string_lossy().to_string().into(),
+                mismatch.extension().into(),
+                mismatch.content_type().name().into(),
+            ]
+        })
+        .collect();
+    Some(Table {
+        name: "Type Mismatches",
+        headers: vec!["Path", "Extension", "Detected type"],
+        rows,
+    })
+}
+
+///One row per path, sets of hard links follow the duplicate sets
+fn duplicates_table(info: &AnalyzedInfo) -> Option<Table> {
+    let duplicates = info.duplicates()?;
+    let headers = vec!["Set", "Kind", "Size(bytes)", "Wasted bytes", "Path"];
+    let sets = duplicates
+        .duplicate_sets()
+        .iter()
+        .map(|set| ("Duplicate", set))
+        .chain(
+            duplicates
+                .hard_link_sets()
+                .iter()
+                .map(|set| ("Hard link", set)),
+        );
+    let rows = sets
+        .enumerate()
+        .flat_map(|(i, (kind, set))| {
+            set.paths().iter().map(move |path| {
+                vec![
+                    (i as u64 + 1).into(),
+                    kind.into(),
+                    Cell::Bytes(set.size()),
+                    Cell::Bytes(set.wasted_bytes()),
+                    path.to_string_lossy().to_string().into(),
+                ]
+            })
+        })
+        .collect();
+    Some(Table {
+        name: "Duplicates",
+        headers,
+        rows,
+    })
+}
+
+fn broken_symlinks_table(info: &AnalyzedInfo) -> Option<Table> {
+    let symlink_info = info.found_symlinks()?;
+    Some(Table {
+        name: "Broken Symlinks",
+        headers: vec!["Path", "Target"],
+        rows: symlink_info
+            .broken()
+            .iter()
+            .map(|broken| {
+                vec![
+                    broken.path().to_string_lossy().to_string().into(),
+                    broken.target().to_string_lossy().to_string().into(),
+                ]
+            })
+            .collect(),
+    })
+}
+
+fn mount_points_table(info: &AnalyzedInfo) -> Option<Table> {
+    let mount_points = info.skipped_mount_points()?;
+    Some(Table {
+        name: "Skipped Mount Points",
+        headers: vec!["Path"],
+        rows: mount_points
+            .iter()
+            .map(|path| vec![path.to_string_lossy().to_string().into()])
+            .collect(),
+    })
+}
+
+///Only included if there were errors
+fn errors_table(info: &AnalyzedInfo) -> Option<Table> {
+    if info.errors().is_empty() {
+        return None;
+    }
+    Some(Table {
+        name: "Errors",
+        headers: vec!["Path", "Kind", "Message"],
+        rows: info
+            .errors()
+            .iter()
+            .map(|error| {
+                vec![
+                    error.path().to_string_lossy().to_string().into(),
+                    format!("{:?}", error.kind()).into(),
+                    error.message().into(),
+                ]
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///Minimal RFC 4180 reader used to check that written fields can be read back
+    fn parse_csv(s: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, in_quotes) {
+                ('"', true) if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                ('"', true) => in_quotes = false,
+                ('"', false) => in_quotes = true,
+                (',', false) => row.push(std::mem::take(&mut field)),
+                ('\r', false) if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (c, _) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn csv_round_trip() {
+        let paths = [
+            "plain/path.txt",
+            "has,comma.txt",
+            "has \"quotes\".txt",
+            "\"starts with quote",
+            "line\r\nbreak, and \"both\"",
+        ];
+        let table = Table {
+            name: "File Info",
+            headers: vec!["Path", "Size"],
+            rows: paths
+                .iter()
+                .map(|p| vec![Cell::from(*p), Cell::from(1_u64)])
+                .collect(),
+        };
+        let parsed = parse_csv(&table.to_csv());
+        assert_eq!(parsed[0], vec!["Path", "Size"]);
+        for (row, path) in parsed[1..].iter().zip(paths) {
+            assert_eq!(row, &vec![path.to_string(), "1".to_string()]);
+        }
+        assert_eq!(table.csv_file_name(), "file_info.csv");
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::bail;
+use serde::Serialize;
+
+use super::{format_size, serialize_path_lossy, AgeTime, DisplaySizes, SizeUnits};
+
+///Upper limit of an age range, e.g. `1w` for files less than a week old.
+///
+///Units are h(hours), d(days), w(weeks), m(months of 30 days) and y(years of 365 days)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgeLimit {
+    ///As it was given, used to label the range
+    label: String,
+    secs: u64,
+}
+
+impl AgeLimit {
+    ///The age ranges used when none are given, <1d, <1w, <1m, <1y and older
+    pub fn defaults() -> Vec<AgeLimit> {
+        ["1d", "1w", "1m", "1y"]
+            .iter()
+            .map(|l| l.parse().expect("default age limits are valid"))
+            .collect()
+    }
+
+    pub fn secs(&self) -> u64 {
+        self.secs
+    }
+}
+
+impl FromStr for AgeLimit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(unit) = s.chars().last() else {
+            bail!("age limit is empty");
+        };
+        let unit_secs = match unit {
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            'm' => 30 * 24 * 60 * 60,
+            'y' => 365 * 24 * 60 * 60,
+            _ => bail!("\"{s}\" needs to end in a unit, one of h, d, w, m or y"),
+        };
+        let count: u64 = s[..s.len() - 1].parse()?;
+        let Some(secs) = count.checked_mul(unit_secs) else {
+            bail!("\"{s}\" is too long an age");
+        };
+        Ok(Self {
+            label: s.to_string(),
+            secs,
+        })
+    }
+}
+
+///Files whose age is in one range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgeBucket {
+    ///e.g. "<1w", the last bucket is ">=" the largest limit
+    label: String,
+    files: u32,
+    bytes: u64,
+}
+
+impl AgeBucket {
+    ///e.g. "<1w", the last bucket is ">=" the largest limit
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn files(&self) -> u32 {
+        self.files
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+///Count and bytes of files in each age range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgeHistogram {
+    ///Which timestamp the ages are based on
+    time: AgeTime,
+    ///Upper limit of each bucket in seconds, the last bucket has no limit
+    #[serde(skip)]
+    limits: Vec<u64>,
+    buckets: Vec<AgeBucket>,
+}
+
+impl AgeHistogram {
+    ///One bucket per limit, smallest first, plus one for anything older
+    pub fn new(time: AgeTime, limits: &[AgeLimit]) -> Self {
+        let mut limits = limits.to_vec();
+        limits.sort_by_key(|l| l.secs);
+        limits.dedup_by_key(|l| l.secs);
+        let mut buckets: Vec<_> = limits
+            .iter()
+            .map(|l| AgeBucket {
+                label: format!("<{}", l.label),
+                files: 0,
+                bytes: 0,
+            })
+            .collect();
+        buckets.push(AgeBucket {
+            label: match limits.last() {
+                Some(l) => format!(">={}", l.label),
+                None => "all".to_string(),
+            },
+            files: 0,
+            bytes: 0,
+        });
+        Self {
+            time,
+            limits: limits.iter().map(|l| l.secs).collect(),
+            buckets,
+        }
+    }
+
+    ///Which timestamp the ages are based on
+    pub fn time(&self) -> AgeTime {
+        self.time
+    }
+
+    pub fn buckets(&self) -> &[AgeBucket] {
+        &self.buckets
+    }
+
+    ///Adds a file that is `age_secs` old
+    pub fn add(&mut self, age_secs: u64, bytes: u64) {
+        let i = self.limits.partition_point(|&limit| limit <= age_secs);
+        self.buckets[i].files += 1;
+        self.buckets[i].bytes += bytes;
+    }
+
+    ///Adds the files counted by another thread, both must have the same buckets
+    pub fn merge(&mut self, other: &AgeHistogram) {
+        for (bucket, other) in self.buckets.iter_mut().zip(&other.buckets) {
+            bucket.files += other.files;
+            bucket.bytes += other.bytes;
+        }
+    }
+}
+
+impl std::fmt::Display for AgeHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for AgeHistogram {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        for bucket in &self.buckets {
+            write!(
+                f,
+                "\n    {}: {} files, {}",
+                bucket.label,
+                bucket.files,
+                format_size(bucket.bytes, units)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+///This is for holding info about the oldest and newest files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileTimeRecord {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    path: PathBuf,
+    ///Seconds since the Unix epoch, negative if before it
+    time: i64,
+}
+
+impl FileTimeRecord {
+    pub fn new(path: PathBuf, time: i64) -> Self {
+        Self { path, time }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///Seconds since the Unix epoch, negative if before it
+    pub fn time(&self) -> i64 {
+        self.time
+    }
+
+    ///If this record should replace `other` as the oldest file.
+    ///
+    ///Ties are broken by path so the result does not depend on the order files are found in
+    pub fn is_older_than(&self, other: &FileTimeRecord) -> bool {
+        self.time < other.time || (self.time == other.time && self.path < other.path)
+    }
+
+    ///If this record should replace `other` as the newest file.
+    ///
+    ///Ties are broken by path so the result does not depend on the order files are found in
+    pub fn is_newer_than(&self, other: &FileTimeRecord) -> bool {
+        self.time > other.time || (self.time == other.time && self.path < other.path)
+    }
+}
+
+impl std::fmt::Display for FileTimeRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\n    Path:{}\n    Time: {}",
+            self.path.to_string_lossy(),
+            format_timestamp(self.time)
+        )
+    }
+}
+
+///Formats seconds since the Unix epoch as e.g. "2024-03-01 13:45:00 UTC"
+pub fn format_timestamp(secs: i64) -> String {
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+    //Converts days since the epoch to a date in the proleptic Gregorian calendar,
+    //see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
@@ -1,14 +1,20 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
 
-const L_ABOUT:&str = "This program is used to analyze a directory and tell you about the contents.";
+use super::{AgeLimit, ExtensionAlias};
+
+const L_ABOUT: &str =
+    "This program is used to analyze a directory and tell you about the contents.";
 
 #[derive(Parser, Debug)]
 #[command(version,about,long_about = L_ABOUT)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
     ///Requied path to analyze
-    path_to_analyze: PathBuf,
+    #[arg(required = true)]
+    path_to_analyze: Option<PathBuf>,
 
     ///Just look at the files in the current directory
     #[arg(short, long, default_value_t = false)]
@@ -19,8 +25,9 @@ pub struct Args {
     file_info: bool,
 
     ///Follow symlinks as if they were a normal object.
-    /// 
-    /// PERF NOTE: For very large directories(>100,000 files or directories) this can be slow to process.
+    ///
+    /// Every object found is tracked by its file id(device and inode) so it is only counted once,
+    /// even if it can be reached through several symlinks, hard links or bind mounts.
     #[arg(short = 's', long, default_value_t = false)]
     follow_symlinks: bool,
 
@@ -36,6 +43,10 @@ pub struct Args {
     #[arg(short, long)]
     export_xlsx: Option<PathBuf>,
 
+    ///Export info as CSV files to the directory specified, one file per xlsx worksheet
+    #[arg(long)]
+    export_csv: Option<PathBuf>,
+
     ///When displaying paths should they be printed in full
     #[arg(short = 'p', long, default_value_t = false)]
     full_path: bool,
@@ -44,6 +55,36 @@ pub struct Args {
     #[arg(short = 'i', long)]
     ignore_entries: Option<String>,
 
+    ///Glob pattern of entries that will not be included in the analysis, can be given multiple times.
+    ///
+    ///Patterns without a `/` match at any depth(e.g. `*.o`, `node_modules`), a leading `/` anchors
+    ///the pattern to the path to analyze and a trailing `/` only matches directories(e.g. `target/`).
+    ///`**` matches any number of directories(e.g. `**/build/*.log`).
+    #[arg(short = 'g', long = "ignore-glob", value_name = "GLOB")]
+    ignore_globs: Vec<String>,
+
+    ///Regex of entries that will not be included in the analysis, can be given multiple times.
+    ///
+    ///Matched against the path of each entry relative to the path to analyze, using `/` as the separator
+    #[arg(long = "ignore-regex", value_name = "REGEX")]
+    ignore_regexes: Vec<String>,
+
+    ///Leave out entries ignored by .gitignore, .ignore and .git/info/exclude files found while
+    ///walking the tree, or above it up to the root of the repository it is in, as well as .git
+    ///itself. Works together with the other ignore options
+    #[arg(long, default_value_t = false)]
+    respect_gitignore: bool,
+
+    ///Don't descend into directories on other filesystems, such as /proc or network mounts.
+    ///The mount points that were skipped are listed in the report
+    #[arg(short = 'x', long, default_value_t = false)]
+    one_file_system: bool,
+
+    ///Stop at the first entry that can't be analyzed, such as a directory that can't be read.
+    ///By default these are listed in the report and the rest of the tree is still analyzed
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
     ///Provides periodic updates about how many entries have been analyized. Value is seconds between updates.
     #[arg(short, long)]
     updates: Option<u64>,
@@ -51,6 +92,316 @@ pub struct Args {
     ///Displays the time the program took to run to stdout
     #[arg(short, long, default_value_t = false)]
     time: bool,
+
+    ///Number of threads used to walk the directory tree
+    #[arg(short = 'j', long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    threads: u16,
+
+    ///Count special files, such as FIFOs, sockets and block and character devices
+    #[arg(long, default_value_t = false)]
+    special_files: bool,
+
+    ///Group files by how long ago they were modified, or accessed or changed with --age-time.
+    ///The oldest and newest file of each type are shown with --file-info
+    #[arg(long, default_value_t = false)]
+    age: bool,
+
+    ///Which time of each file is used by --age
+    #[arg(long, value_enum, default_value_t = AgeTime::Modified)]
+    age_time: AgeTime,
+
+    ///Comma separated upper limits of the age ranges used by --age, files older than the largest
+    ///limit are grouped together. Units are h(hours), d(days), w(weeks), m(30 days) and y(365 days)
+    #[arg(
+        long,
+        value_name = "LIMITS",
+        value_delimiter = ',',
+        default_value = "1d,1w,1m,1y"
+    )]
+    age_buckets: Vec<AgeLimit>,
+
+    ///Group files into log scale size ranges(0, <1K, <4K, <64K, <1M, <100M and >=100M) and show
+    ///the mean and median size. The median is estimated to within 1% so every size doesn't need
+    ///to be kept. Combined with --file-info this is also done for each type
+    #[arg(long, default_value_t = false)]
+    size_histogram: bool,
+
+    ///Count the files and bytes owned by each user and group, using --size-basis. Users and
+    ///groups that no longer exist are shown as orphaned. Only on Unix
+    #[arg(long, default_value_t = false)]
+    by_owner: bool,
+
+    ///List world-writable files and directories, setuid and setgid files, world-writable
+    ///directories without the sticky bit, files with the sticky bit and files and directories
+    ///owned by a different user than their directory. Only on Unix
+    #[arg(long, default_value_t = false)]
+    audit_permissions: bool,
+
+    ///Read the first bytes of each file to detect its type(elf, pe, zip, gzip, png, jpeg, pdf,
+    ///sqlite, text or empty) and report files whose extension does not match. With --file-info
+    ///files are grouped by detected type instead of extension, files of an unknown type are
+    ///grouped as unknown:<extension>
+    #[arg(long, default_value_t = false)]
+    detect_type: bool,
+
+    ///Show the size of each directory including everything below it, sorted by size, using
+    ///--size-basis
+    #[arg(short, long, default_value_t = false)]
+    dir_sizes: bool,
+
+    ///How many levels below the path to analyze are shown by --dir-sizes
+    #[arg(long, default_value_t = 1)]
+    depth: usize,
+
+    ///Show the N largest files found. Combined with --file-info the N largest files of each type are also shown
+    #[arg(long, value_name = "N")]
+    top: Option<usize>,
+
+    ///Find files with the same content and how many bytes they waste. Files are compared byte
+    ///for byte before being reported. Hard links are listed separately since they take up no
+    ///extra space
+    #[arg(long, default_value_t = false)]
+    find_duplicates: bool,
+
+    ///Also show the space files take up on disk, which can differ from their size because of
+    ///sparse files and filesystem block overhead. Only known on Unix, elsewhere it is the same as the size
+    #[arg(long, default_value_t = false)]
+    allocated_size: bool,
+
+    ///Which size of each file is used for percentages and the largest/smallest files.
+    ///`allocated` implies --allocated-size
+    #[arg(long, value_enum, default_value_t = SizeBasis::Apparent)]
+    size_basis: SizeBasis,
+
+    ///Show sizes in the text output in kB/MB/...(si) or KiB/MiB/...(iec) instead of bytes, iec if
+    ///no units are given. The xlsx export keeps sizes in bytes and only shows them in kB/MB/GB with
+    ///a number format, for either units
+    #[arg(long, value_enum, value_name = "UNITS", num_args = 0..=1, default_missing_value = "iec")]
+    human_readable: Option<SizeUnits>,
+
+    ///Order the file types are shown in by --file-info, ties are broken by name
+    #[arg(long, value_enum, value_name = "KEY", default_value_t = SortBy::Size)]
+    sort_by: SortBy,
+
+    ///Order used by --sort-by, ascending for `name` and descending otherwise if not given
+    #[arg(long, value_enum, value_name = "ORDER")]
+    sort_order: Option<SortOrder>,
+
+    ///Comma separated normalizations of the extensions --file-info groups files by: case(JPG is
+    ///counted as jpg), aliases(jpeg as jpg, htm as html, yml as yaml, ...) and compound(.tar.gz,
+    ///.tar.zst and .d.ts are kept whole). All of them if none are given
+    #[arg(
+        long,
+        value_enum,
+        value_name = "PARTS",
+        value_delimiter = ',',
+        num_args = 0..=1,
+        default_missing_value = "case,aliases,compound"
+    )]
+    normalize_extensions: Vec<ExtensionNormalization>,
+
+    ///Count files with one extension as another in --file-info, e.g. `--extension-alias jpeg=jpg`,
+    ///can be given multiple times. Replaces the built in alias of --normalize-extensions for the
+    ///same extension
+    #[arg(long = "extension-alias", value_name = "FROM=TO")]
+    extension_aliases: Vec<ExtensionAlias>,
+
+    ///Total the files and bytes in each category such as image, video, source code and archive,
+    ///using --size-basis. Files are put in a category by their extension after
+    ///--normalize-extensions, or by the type found by --detect-type if the extension is not in one
+    #[arg(long, default_value_t = false)]
+    categories: bool,
+
+    ///TOML file of categories that are added to or replace the built in ones, implies
+    ///--categories. See the ReadMe for the format
+    #[arg(long, value_name = "FILE")]
+    category_file: Option<PathBuf>,
+
+    ///Format of the output
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    ///Write the output to a file at the location specified instead of stdout
+    #[arg(short, long)]
+    output_file: Option<PathBuf>,
+
+    ///Save a snapshot of the analysis to the file specified, which can be compared to a later one
+    ///with `dira diff`. Holds the totals of each extension and directory, and with
+    ///--snapshot-files the size of every file
+    #[arg(long, value_name = "FILE")]
+    save_snapshot: Option<PathBuf>,
+
+    ///Include the path and size of every file in the snapshot, needed for `dira diff` to list the
+    ///files that were added, removed or grew
+    #[arg(long, default_value_t = false, requires = "save_snapshot")]
+    snapshot_files: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    ///Compare two snapshots saved with --save-snapshot
+    Diff(DiffArgs),
+}
+
+///Options of `dira diff`
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    ///The earlier snapshot
+    old_snapshot: PathBuf,
+
+    ///The later snapshot
+    new_snapshot: PathBuf,
+
+    ///Export the differences to a xlsx file at the location specified
+    #[arg(short, long)]
+    export_xlsx: Option<PathBuf>,
+
+    ///Show sizes in kB/MB/...(si) or KiB/MiB/...(iec) instead of bytes, iec if no units are given
+    #[arg(long, value_enum, value_name = "UNITS", num_args = 0..=1, default_missing_value = "iec")]
+    human_readable: Option<SizeUnits>,
+
+    ///Format of the output
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+
+    ///Write the output to a file at the location specified instead of stdout
+    #[arg(short, long)]
+    output_file: Option<PathBuf>,
+}
+
+impl DiffArgs {
+    pub fn new(old_snapshot: PathBuf, new_snapshot: PathBuf) -> Self {
+        Self {
+            old_snapshot,
+            new_snapshot,
+            export_xlsx: None,
+            human_readable: None,
+            output_format: OutputFormat::default(),
+            output_file: None,
+        }
+    }
+
+    ///The earlier snapshot
+    pub fn old_snapshot(&self) -> &PathBuf {
+        &self.old_snapshot
+    }
+
+    ///The later snapshot
+    pub fn new_snapshot(&self) -> &PathBuf {
+        &self.new_snapshot
+    }
+
+    ///Export the differences to a xlsx file at the location specified
+    pub fn export_xlsx(&self) -> Option<&PathBuf> {
+        self.export_xlsx.as_ref()
+    }
+
+    ///Units sizes are shown in, `None` for plain bytes
+    pub fn human_readable(&self) -> Option<SizeUnits> {
+        self.human_readable
+    }
+
+    ///Format of the output
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    pub fn set_output_format(&mut self, output_format: OutputFormat) {
+        self.output_format = output_format;
+    }
+
+    ///Write the output to a file at the location specified instead of stdout
+    pub fn output_file(&self) -> Option<&PathBuf> {
+        self.output_file.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    ///Human readable text
+    #[default]
+    Text,
+    ///JSON, see ReadMe.txt for the schema
+    Json,
+}
+
+///Which size of a file is used when comparing files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeBasis {
+    ///The length of the file, as shown by `ls -l`
+    #[default]
+    Apparent,
+    ///The space the file takes up on disk, as shown by `du`
+    Allocated,
+}
+
+///Units used to show sizes in a human readable way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SizeUnits {
+    ///Powers of 1000: kB, MB, GB, ...
+    Si,
+    ///Powers of 1024: KiB, MiB, GiB, ...
+    Iec,
+}
+
+///What file types are ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SortBy {
+    ///Total size of the files of each type, using --size-basis
+    #[default]
+    Size,
+    ///Number of files of each type
+    Count,
+    ///The extension
+    Name,
+    ///Size of the largest file of each type
+    Largest,
+}
+
+///A way the extensions files are grouped by can be normalized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExtensionNormalization {
+    ///Fold extensions to lowercase
+    Case,
+    ///Count aliases such as jpeg or htm as jpg or html
+    Aliases,
+    ///Keep extensions made of more than one part whole, such as .tar.gz
+    Compound,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+///Which time of a file its age is based on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AgeTime {
+    ///When the contents were last changed
+    #[default]
+    Modified,
+    ///When the file was last read, not updated by every filesystem
+    Accessed,
+    ///When the contents or metadata were last changed, on Windows the time the file was created
+    Changed,
+}
+
+impl AgeTime {
+    ///Name of the time as shown in the output
+    pub fn label(&self) -> &'static str {
+        match self {
+            AgeTime::Modified => "modified",
+            AgeTime::Accessed => "accessed",
+            AgeTime::Changed => "changed",
+        }
+    }
 }
 
 impl Args {
@@ -69,7 +420,7 @@ impl Args {
         time: bool,
     ) -> Self {
         Self {
-            path_to_analyze,
+            path_to_analyze: Some(path_to_analyze),
             no_recurse,
             file_info,
             follow_symlinks,
@@ -80,12 +431,47 @@ impl Args {
             updates,
             count_symlinks,
             time,
+            threads: 1,
+            output_format: OutputFormat::default(),
+            output_file: None,
+            export_csv: None,
+            special_files: false,
+            age: false,
+            size_histogram: false,
+            by_owner: false,
+            audit_permissions: false,
+            detect_type: false,
+            age_time: AgeTime::default(),
+            age_buckets: AgeLimit::defaults(),
+            dir_sizes: false,
+            depth: 1,
+            top: None,
+            ignore_globs: Vec::new(),
+            ignore_regexes: Vec::new(),
+            respect_gitignore: false,
+            one_file_system: false,
+            strict: false,
+            find_duplicates: false,
+            allocated_size: false,
+            size_basis: SizeBasis::default(),
+            human_readable: None,
+            sort_by: SortBy::default(),
+            sort_order: None,
+            normalize_extensions: Vec::new(),
+            extension_aliases: Vec::new(),
+            categories: false,
+            category_file: None,
+            save_snapshot: None,
+            snapshot_files: false,
+            command: None,
         }
     }
 
     ///Requied path to analyze
     pub fn path_to_analyze(&self) -> &PathBuf {
-        &self.path_to_analyze
+        self.path_to_analyze
+            .as_ref()
+            .expect("clap requires the path unless a subcommand is given")
     }
 
     ///Just look at the files in the current directory
@@ -112,6 +498,11 @@ impl Args {
         self.export_xlsx.as_ref()
     }
 
+    ///Export info as CSV files to the directory specified, one file per xlsx worksheet
+    pub fn export_csv(&self) -> Option<&PathBuf> {
+        self.export_csv.as_ref()
+    }
+
     ///When displaying paths should they be printed in full
     pub fn full_path(&self) -> bool {
         self.full_path
@@ -122,6 +513,51 @@ impl Args {
         self.ignore_entries.as_ref()
     }
 
+    ///Glob patterns of entries that will not be included in the analysis
+    pub fn ignore_globs(&self) -> &[String] {
+        &self.ignore_globs
+    }
+
+    pub fn set_ignore_globs(&mut self, ignore_globs: Vec<String>) {
+        self.ignore_globs = ignore_globs;
+    }
+
+    ///Regexes of entries that will not be included in the analysis
+    pub fn ignore_regexes(&self) -> &[String] {
+        &self.ignore_regexes
+    }
+
+    pub fn set_ignore_regexes(&mut self, ignore_regexes: Vec<String>) {
+        self.ignore_regexes = ignore_regexes;
+    }
+
+    ///Leave out entries ignored by .gitignore, .ignore and .git/info/exclude files
+    pub fn respect_gitignore(&self) -> bool {
+        self.respect_gitignore
+    }
+
+    pub fn set_respect_gitignore(&mut self, respect_gitignore: bool) {
+        self.respect_gitignore = respect_gitignore;
+    }
+
+    ///Don't descend into directories on other filesystems
+    pub fn one_file_system(&self) -> bool {
+        self.one_file_system
+    }
+
+    pub fn set_one_file_system(&mut self, one_file_system: bool) {
+        self.one_file_system = one_file_system;
+    }
+
+    ///Stop at the first entry that can't be analyzed
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
     ///Provides periodic updates about how many entries have been analyized. Value in Option is seconds between updates.
     pub fn updates(&self) -> Option<u64> {
         self.updates
@@ -136,4 +572,235 @@ impl Args {
     pub fn time(&self) -> bool {
         self.time
     }
+
+    ///Number of threads used to walk the directory tree
+    pub fn threads(&self) -> usize {
+        self.threads as usize
+    }
+
+    pub fn set_threads(&mut self, threads: u16) {
+        self.threads = threads.max(1);
+    }
+
+    ///Count special files, such as FIFOs, sockets and block and character devices
+    pub fn special_files(&self) -> bool {
+        self.special_files
+    }
+
+    pub fn set_special_files(&mut self, special_files: bool) {
+        self.special_files = special_files;
+    }
+
+    ///Group files by how long ago they were modified, accessed or changed
+    pub fn age(&self) -> bool {
+        self.age
+    }
+
+    pub fn set_age(&mut self, age: bool) {
+        self.age = age;
+    }
+
+    ///Which time of each file is used by --age
+    pub fn age_time(&self) -> AgeTime {
+        self.age_time
+    }
+
+    ///Upper limits of the age ranges used by --age
+    pub fn age_buckets(&self) -> &[AgeLimit] {
+        &self.age_buckets
+    }
+
+    pub fn set_age_buckets(&mut self, age_buckets: Vec<AgeLimit>) {
+        self.age_buckets = age_buckets;
+    }
+
+    ///Group files into log scale size ranges and show the mean and median size
+    pub fn size_histogram(&self) -> bool {
+        self.size_histogram
+    }
+
+    pub fn set_size_histogram(&mut self, size_histogram: bool) {
+        self.size_histogram = size_histogram;
+    }
+
+    ///Count the files and bytes owned by each user and group
+    pub fn by_owner(&self) -> bool {
+        self.by_owner
+    }
+
+    pub fn set_by_owner(&mut self, by_owner: bool) {
+        self.by_owner = by_owner;
+    }
+
+    ///List entries with risky or unusual permissions
+    pub fn audit_permissions(&self) -> bool {
+        self.audit_permissions
+    }
+
+    pub fn set_audit_permissions(&mut self, audit_permissions: bool) {
+        self.audit_permissions = audit_permissions;
+    }
+
+    ///Detect the type of each file from its content
+    pub fn detect_type(&self) -> bool {
+        self.detect_type
+    }
+
+    pub fn set_detect_type(&mut self, detect_type: bool) {
+        self.detect_type = detect_type;
+    }
+
+    ///Show the size of each directory including everything below it, sorted by size
+    pub fn dir_sizes(&self) -> bool {
+        self.dir_sizes
+    }
+
+    pub fn set_dir_sizes(&mut self, dir_sizes: bool) {
+        self.dir_sizes = dir_sizes;
+    }
+
+    ///How many levels below the path to analyze are shown by --dir-sizes
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+    }
+
+    ///Show the N largest files found. Combined with --file-info the N largest files of each type are also shown
+    pub fn top(&self) -> Option<usize> {
+        self.top
+    }
+
+    pub fn set_top(&mut self, top: Option<usize>) {
+        self.top = top;
+    }
+
+    ///Find files with the same content and how many bytes they waste
+    pub fn find_duplicates(&self) -> bool {
+        self.find_duplicates
+    }
+
+    pub fn set_find_duplicates(&mut self, find_duplicates: bool) {
+        self.find_duplicates = find_duplicates;
+    }
+
+    ///Track the space files take up on disk as well as their size
+    pub fn allocated_size(&self) -> bool {
+        self.allocated_size || self.size_basis == SizeBasis::Allocated
+    }
+
+    pub fn set_allocated_size(&mut self, allocated_size: bool) {
+        self.allocated_size = allocated_size;
+    }
+
+    ///Units sizes are shown in, `None` for plain bytes
+    pub fn human_readable(&self) -> Option<SizeUnits> {
+        self.human_readable
+    }
+
+    pub fn set_human_readable(&mut self, human_readable: Option<SizeUnits>) {
+        self.human_readable = human_readable;
+    }
+
+    ///Order the file types are shown in by --file-info
+    pub fn sort_by(&self) -> SortBy {
+        self.sort_by
+    }
+
+    pub fn set_sort_by(&mut self, sort_by: SortBy) {
+        self.sort_by = sort_by;
+    }
+
+    ///Order used by --sort-by, ascending for names and descending otherwise if none was given
+    pub fn sort_order(&self) -> SortOrder {
+        match (self.sort_order, self.sort_by) {
+            (Some(order), _) => order,
+            (None, SortBy::Name) => SortOrder::Asc,
+            (None, _) => SortOrder::Desc,
+        }
+    }
+
+    pub fn set_sort_order(&mut self, sort_order: Option<SortOrder>) {
+        self.sort_order = sort_order;
+    }
+
+    ///How the extensions files are grouped by are normalized, empty if they are used as is
+    pub fn normalize_extensions(&self) -> &[ExtensionNormalization] {
+        &self.normalize_extensions
+    }
+
+    pub fn set_normalize_extensions(&mut self, normalize_extensions: Vec<ExtensionNormalization>) {
+        self.normalize_extensions = normalize_extensions;
+    }
+
+    ///Extensions counted as another one on top of the built in aliases
+    pub fn extension_aliases(&self) -> &[ExtensionAlias] {
+        &self.extension_aliases
+    }
+
+    pub fn set_extension_aliases(&mut self, extension_aliases: Vec<ExtensionAlias>) {
+        self.extension_aliases = extension_aliases;
+    }
+
+    ///Total the files and bytes in each category, also true if a category file was given
+    pub fn categories(&self) -> bool {
+        self.categories || self.category_file.is_some()
+    }
+
+    pub fn set_categories(&mut self, categories: bool) {
+        self.categories = categories;
+    }
+
+    ///TOML file of categories that are added to or replace the built in ones
+    pub fn category_file(&self) -> Option<&PathBuf> {
+        self.category_file.as_ref()
+    }
+
+    pub fn set_category_file(&mut self, category_file: Option<PathBuf>) {
+        self.category_file = category_file;
+    }
+
+    ///Which size of each file is used for percentages and the largest/smallest files
+    pub fn size_basis(&self) -> SizeBasis {
+        self.size_basis
+    }
+
+    pub fn set_size_basis(&mut self, size_basis: SizeBasis) {
+        self.size_basis = size_basis;
+    }
+
+    ///Format of the output
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    ///Write the output to a file at the location specified instead of stdout
+    pub fn output_file(&self) -> Option<&PathBuf> {
+        self.output_file.as_ref()
+    }
+
+    ///Save a snapshot of the analysis to the file specified
+    pub fn save_snapshot(&self) -> Option<&PathBuf> {
+        self.save_snapshot.as_ref()
+    }
+
+    pub fn set_save_snapshot(&mut self, save_snapshot: Option<PathBuf>) {
+        self.save_snapshot = save_snapshot;
+    }
+
+    ///Include the path and size of every file in the snapshot
+    pub fn snapshot_files(&self) -> bool {
+        self.snapshot_files
+    }
+
+    pub fn set_snapshot_files(&mut self, snapshot_files: bool) {
+        self.snapshot_files = snapshot_files;
+    }
+
+    ///The subcommand given instead of a path to analyze, if any
+    pub fn command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
 }
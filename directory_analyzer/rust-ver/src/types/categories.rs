@@ -0,0 +1,288 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use super::{format_percent, format_size, Args, DisplaySizes, FileExtension, SizeUnits, Totals};
+
+///Category of files whose extension is not in any category
+pub const OTHER_CATEGORY: &str = "other";
+
+///Categories used when no --category-file is given, or added to the ones in it.
+///
+///Also has the names --detect-type groups files under, such as elf and jpeg, for files whose
+///extension is not in a category
+const DEFAULT_CATEGORIES: [(&str, &[&str]); 9] = [
+    (
+        "image",
+        &[
+            "jpg", "jpeg", "png", "gif", "bmp", "tif", "tiff", "webp", "svg", "ico", "heic",
+            "heif", "avif", "raw", "cr2", "nef", "arw", "dng", "psd", "xcf",
+        ],
+    ),
+    (
+        "video",
+        &[
+            "mp4", "m4v", "mkv", "mov", "avi", "wmv", "flv", "webm", "mpg", "mpeg", "3gp", "vob",
+            "m2ts", "mts",
+        ],
+    ),
+    (
+        "audio",
+        &[
+            "mp3", "wav", "flac", "aac", "ogg", "oga", "m4a", "wma", "opus", "aif", "aiff", "mid",
+            "midi",
+        ],
+    ),
+    (
+        "document",
+        &[
+            "pdf", "doc", "docx", "odt", "rtf", "txt", "text", "md", "markdown", "rst", "tex",
+            "epub", "xls", "xlsx", "ods", "ppt", "pptx", "odp", "pages", "numbers",
+        ],
+    ),
+    (
+        "archive",
+        &[
+            "zip", "tar", "gz", "gzip", "tgz", "bz2", "xz", "zst", "lz", "lz4", "lzma", "z", "7z",
+            "rar", "cab", "iso", "dmg", "tar.gz", "tar.bz2", "tar.xz", "tar.zst", "tar.lz",
+            "tar.lz4", "tar.lzma", "tar.z",
+        ],
+    ),
+    (
+        "source code",
+        &[
+            "rs", "c", "h", "cc", "cpp", "cxx", "hpp", "hh", "cs", "java", "kt", "kts", "scala",
+            "go", "py", "pyi", "rb", "php", "pl", "lua", "swift", "m", "mm", "js", "mjs", "cjs",
+            "jsx", "ts", "tsx", "d.ts", "d.mts", "d.cts", "vue", "svelte", "html", "htm", "css",
+            "scss", "sass", "less", "sh", "bash", "zsh", "fish", "ps1", "bat", "cmd", "sql", "hs",
+            "ex", "exs", "erl", "clj", "dart", "zig", "asm", "s",
+        ],
+    ),
+    (
+        "executable",
+        &[
+            "exe", "dll", "so", "dylib", "o", "a", "lib", "ko", "bin", "elf", "pe", "msi",
+            "appimage", "deb", "rpm", "apk", "jar", "class", "wasm",
+        ],
+    ),
+    (
+        "data",
+        &[
+            "json", "jsonl", "ndjson", "xml", "yaml", "yml", "toml", "ini", "cfg", "conf", "csv",
+            "tsv", "parquet", "avro", "db", "sqlite", "sqlite3", "log",
+        ],
+    ),
+    ("font", &["ttf", "otf", "woff", "woff2", "eot", "fon"]),
+];
+
+///Contents of a --category-file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CategoryFile {
+    ///Start from no categories instead of the built in ones
+    #[serde(default)]
+    replace_builtin: bool,
+    ///Name of each category and the extensions in it
+    #[serde(default)]
+    categories: BTreeMap<String, Vec<String>>,
+}
+
+///Which category each extension is in, built from the built in categories and --category-file
+#[derive(Debug, Clone, Default)]
+pub struct CategoryMap {
+    ///Lowercase extension without the leading dot to its category
+    by_extension: HashMap<FileExtension, String>,
+}
+
+impl CategoryMap {
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
+        let file = match args.category_file() {
+            Some(path) => Some(load_category_file(path)?),
+            None => None,
+        };
+        let mut out = Self::default();
+        if !file.as_ref().is_some_and(|file| file.replace_builtin) {
+            for (category, extensions) in DEFAULT_CATEGORIES {
+                out.insert(category, extensions.iter().copied());
+            }
+        }
+        //After the built in ones so an extension listed in the file moves to its category there
+        if let Some(file) = &file {
+            for (category, extensions) in &file.categories {
+                out.insert(category, extensions.iter().map(|ext| ext.as_str()));
+            }
+        }
+        Ok(out)
+    }
+
+    fn insert<'a>(&mut self, category: &str, extensions: impl Iterator<Item = &'a str>) {
+        for ext in extensions {
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            self.by_extension.insert(ext, category.to_string());
+        }
+    }
+
+    ///The category of files with extension `ext`, `OTHER_CATEGORY` if it is not in one
+    pub fn category(&self, ext: &str) -> &str {
+        self.find(ext).unwrap_or(OTHER_CATEGORY)
+    }
+
+    ///The category of files with extension `ext` if it is in one
+    pub fn find(&self, ext: &str) -> Option<&str> {
+        self.by_extension
+            .get(&ext.to_lowercase())
+            .map(|category| category.as_str())
+    }
+}
+
+fn load_category_file(path: &Path) -> anyhow::Result<CategoryFile> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("could not read category file {}", path.to_string_lossy()))?;
+    toml::from_str(&contents)
+        .with_context(|| format!("{} is not a valid category file", path.to_string_lossy()))
+}
+
+///Files in one category
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryTotals {
+    name: String,
+    #[serde(flatten)]
+    totals: Totals,
+    percent_of_total_files: f32,
+    percent_of_total_size: f32,
+}
+
+impl CategoryTotals {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn totals(&self) -> Totals {
+        self.totals
+    }
+
+    ///Fraction of all files that are in this category
+    pub fn percent_of_total_files(&self) -> f32 {
+        self.percent_of_total_files
+    }
+
+    ///Fraction of the size of all files taken up by this category, using --size-basis
+    pub fn percent_of_total_size(&self) -> f32 {
+        self.percent_of_total_size
+    }
+}
+
+impl std::fmt::Display for CategoryTotals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for CategoryTotals {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} files({}%), {}({}%)",
+            self.name,
+            self.totals.files(),
+            format_percent(self.percent_of_total_files),
+            format_size(self.totals.bytes(), units),
+            format_percent(self.percent_of_total_size)
+        )
+    }
+}
+
+///Number of files and bytes in each category, see --categories
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct CategoryInfo {
+    ///Gathered while walking and turned into `categories` by `finish`
+    #[serde(skip)]
+    by_name: HashMap<String, Totals>,
+    ///Most bytes first
+    categories: Vec<CategoryTotals>,
+}
+
+impl CategoryInfo {
+    pub fn add(&mut self, category: &str, bytes: u64) {
+        match self.by_name.get_mut(category) {
+            Some(totals) => totals.add(bytes),
+            None => {
+                let mut totals = Totals::default();
+                totals.add(bytes);
+                self.by_name.insert(category.to_string(), totals);
+            }
+        }
+    }
+
+    ///Adds the files counted by another thread
+    pub fn merge(&mut self, other: CategoryInfo) {
+        for (name, totals) in other.by_name {
+            self.by_name.entry(name).or_default().merge(totals);
+        }
+    }
+
+    ///Calculates the percentages and sorts the categories once all files are added, most bytes
+    ///first and ties by name so the order is the same on every run
+    pub fn finish(&mut self) {
+        let total = self
+            .by_name
+            .values()
+            .fold(Totals::default(), |mut total, totals| {
+                total.merge(*totals);
+                total
+            });
+        let fraction = |part: u64, whole: u64| match whole {
+            0 => 0.0,
+            whole => part as f32 / whole as f32,
+        };
+        self.categories = std::mem::take(&mut self.by_name)
+            .into_iter()
+            .map(|(name, totals)| CategoryTotals {
+                name,
+                totals,
+                percent_of_total_files: fraction(totals.files(), total.files()),
+                percent_of_total_size: fraction(totals.bytes(), total.bytes()),
+            })
+            .collect();
+        self.categories.sort_by(|a, b| {
+            b.totals
+                .bytes()
+                .cmp(&a.totals.bytes())
+                .then_with(|| a.name.cmp(&b.name))
+        });
+    }
+
+    ///Most bytes first
+    pub fn categories(&self) -> &[CategoryTotals] {
+        &self.categories
+    }
+}
+
+impl std::fmt::Display for CategoryInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for CategoryInfo {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(f, "\nCategories:")?;
+        for category in &self.categories {
+            write!(f, "\n  {}", category.with_units(units))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::{serialize_path_lossy, FileExtension};
+
+///How many leading bytes of a file are read to detect its type
+const SNIFF_LEN: usize = 1024;
+
+///The format of a file going by its leading bytes, used by --detect-type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    Elf,
+    ///Windows or DOS executable
+    Pe,
+    Zip,
+    Gzip,
+    Png,
+    Jpeg,
+    Pdf,
+    Sqlite,
+    ///UTF-8 without any NUL bytes
+    Text,
+    Empty,
+    ///None of the above
+    Unknown,
+}
+
+impl ContentType {
+    ///Detects the type of the file at `path` from its first bytes
+    pub fn detect(path: &Path) -> io::Result<Self> {
+        let mut buf = Vec::with_capacity(SNIFF_LEN);
+        File::open(path)?
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut buf)?;
+        Ok(Self::from_bytes(&buf))
+    }
+
+    ///Detects the type of a file that starts with `bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        const MAGIC: [(&[u8], ContentType); 9] = [
+            (b"\x7fELF", ContentType::Elf),
+            (b"PK\x03\x04", ContentType::Zip),
+            //An empty archive
+            (b"PK\x05\x06", ContentType::Zip),
+            (b"\x1f\x8b", ContentType::Gzip),
+            (b"\x89PNG\r\n\x1a\n", ContentType::Png),
+            (b"\xff\xd8\xff", ContentType::Jpeg),
+            (b"%PDF-", ContentType::Pdf),
+            (b"SQLite format 3\0", ContentType::Sqlite),
+            (b"\xef\xbb\xbf", ContentType::Text),
+        ];
+        if bytes.is_empty() {
+            return ContentType::Empty;
+        }
+        if let Some((_, content_type)) = MAGIC.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+            return *content_type;
+        }
+        if is_pe(bytes) {
+            return ContentType::Pe;
+        }
+        let is_utf8 = match std::str::from_utf8(bytes) {
+            Ok(_) => true,
+            //A character cut off by the end of the buffer
+            Err(e) => e.error_len().is_none(),
+        };
+        match is_utf8 && !bytes.contains(&0) {
+            true => ContentType::Text,
+            false => ContentType::Unknown,
+        }
+    }
+
+    ///Name used for the type in the output, also its key in file_info
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentType::Elf => "elf",
+            ContentType::Pe => "pe",
+            ContentType::Zip => "zip",
+            ContentType::Gzip => "gzip",
+            ContentType::Png => "png",
+            ContentType::Jpeg => "jpeg",
+            ContentType::Pdf => "pdf",
+            ContentType::Sqlite => "sqlite",
+            ContentType::Text => "text",
+            ContentType::Empty => "empty",
+            ContentType::Unknown => "unknown",
+        }
+    }
+
+    ///Extensions that files of this type are expected to have, lowercase
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            ContentType::Elf => &["so", "o", "ko", "elf", "bin", "out", "axf", "prx"],
+            ContentType::Pe => &[
+                "exe", "dll", "sys", "efi", "scr", "ocx", "cpl", "drv", "mui",
+            ],
+            ContentType::Zip => &[
+                "zip", "jar", "war", "ear", "apk", "aab", "ipa", "docx", "xlsx", "pptx", "odt",
+                "ods", "odp", "epub", "whl", "nupkg", "xpi", "vsix", "kmz", "3mf",
+            ],
+            ContentType::Gzip => &["gz", "tgz", "svgz", "emz"],
+            ContentType::Png => &["png", "apng"],
+            ContentType::Jpeg => &["jpg", "jpeg", "jpe", "jfif"],
+            ContentType::Pdf => &["pdf", "ai"],
+            ContentType::Sqlite => &["sqlite", "sqlite3", "db", "db3", "s3db", "sl3"],
+            ContentType::Text | ContentType::Empty | ContentType::Unknown => &[],
+        }
+    }
+
+    ///The binary type an extension claims to be, e.g. Png for "png"
+    fn claimed_by(ext: &str) -> Option<Self> {
+        [
+            ContentType::Elf,
+            ContentType::Pe,
+            ContentType::Zip,
+            ContentType::Gzip,
+            ContentType::Png,
+            ContentType::Jpeg,
+            ContentType::Pdf,
+            ContentType::Sqlite,
+        ]
+        .into_iter()
+        .find(|content_type| content_type.extensions().contains(&ext))
+    }
+
+    ///If a file with extension `ext` and this content is misnamed.
+    ///
+    ///Files without an extension make no claim about their content so they never disagree, and
+    ///neither do empty files. Text and unknown content only disagree with an extension of one of
+    ///the binary types
+    pub fn disagrees_with(&self, ext: &str) -> bool {
+        let ext = ext.to_lowercase();
+        match self {
+            _ if ext.is_empty() => false,
+            ContentType::Empty => false,
+            ContentType::Text | ContentType::Unknown => Self::claimed_by(&ext).is_some(),
+            //Versioned shared libraries such as libc.so.6
+            ContentType::Elf if ext.bytes().all(|b| b.is_ascii_digit()) => false,
+            content_type => !content_type.extensions().contains(&ext.as_str()),
+        }
+    }
+
+    ///The key a file with extension `ext` is grouped under in file_info. Files whose content
+    ///could not be identified are grouped under e.g. "unknown:xyz" so formats that are not
+    ///detected are still grouped, without being mixed in with files detected as that type
+    pub fn group_key(&self, ext: FileExtension) -> FileExtension {
+        match self {
+            ContentType::Unknown if ext.is_empty() => self.name().to_string(),
+            ContentType::Unknown => format!("{}:{ext}", self.name()),
+            content_type => content_type.name().to_string(),
+        }
+    }
+}
+
+///If `bytes` start with a DOS header pointing to a PE header. Plenty of other files start with
+///"MZ" so that alone is not enough, the PE header has to be within the bytes read
+fn is_pe(bytes: &[u8]) -> bool {
+    //Offset of the PE header, e_lfanew in the DOS header
+    let Some(offset) = bytes.get(0x3c..0x40) else {
+        return false;
+    };
+    let offset = u32::from_le_bytes(offset.try_into().unwrap()) as usize;
+    bytes.starts_with(b"MZ")
+        && offset
+            .checked_add(4)
+            .and_then(|end| bytes.get(offset..end))
+            .is_some_and(|signature| signature == b"PE\0\0")
+}
+
+impl std::fmt::Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+///A file whose extension does not match its content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TypeMismatch {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    path: PathBuf,
+    extension: FileExtension,
+    content_type: ContentType,
+}
+
+impl TypeMismatch {
+    pub fn new(path: PathBuf, extension: FileExtension, content_type: ContentType) -> Self {
+        Self {
+            path,
+            extension,
+            content_type,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn extension(&self) -> &str {
+        &self.extension
+    }
+
+    pub fn content_type(&self) -> ContentType {
+        self.content_type
+    }
+}
+
+impl std::fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: named {} but contains {}",
+            self.path.to_string_lossy(),
+            self.extension,
+            self.content_type
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pe_needs_pe_header() {
+        let mut exe = vec![0; 0x84];
+        exe[..2].copy_from_slice(b"MZ");
+        exe[0x3c] = 0x80;
+        exe[0x80..].copy_from_slice(b"PE\0\0");
+        assert_eq!(ContentType::from_bytes(&exe), ContentType::Pe);
+
+        //No PE header where e_lfanew points
+        exe[0x3c] = 0x40;
+        assert_eq!(ContentType::from_bytes(&exe), ContentType::Unknown);
+        //Too short to have e_lfanew
+        assert_eq!(ContentType::from_bytes(b"MZ\0\0"), ContentType::Unknown);
+        assert_eq!(
+            ContentType::from_bytes(b"MZ is a text file"),
+            ContentType::Text
+        );
+    }
+}
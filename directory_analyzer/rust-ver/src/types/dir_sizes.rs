@@ -0,0 +1,195 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{ser::SerializeSeq, Serialize, Serializer};
+
+use super::{format_size, serialize_path_lossy, DisplaySizes, SizeUnits};
+
+///Size info about a single directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct DirSize {
+    ///Bytes taken up by the files in this directory, using --size-basis
+    bytes: u64,
+    ///Count of files in this directory
+    files: u32,
+    ///Count of directories in this directory
+    dirs: u32,
+}
+
+impl DirSize {
+    pub fn new(bytes: u64, files: u32, dirs: u32) -> Self {
+        Self { bytes, files, dirs }
+    }
+
+    ///Bytes taken up by the files in this directory, using --size-basis
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    ///Count of files in this directory
+    pub fn files(&self) -> u32 {
+        self.files
+    }
+
+    ///Count of directories in this directory
+    pub fn dirs(&self) -> u32 {
+        self.dirs
+    }
+
+    fn add(&mut self, other: &DirSize) {
+        self.bytes += other.bytes;
+        self.files += other.files;
+        self.dirs += other.dirs;
+    }
+}
+
+///Per directory breakdown of sizes, like `du`.
+///
+///While walking, each directory only holds what is directly inside it. After `roll_up` each
+///directory also includes everything below it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DirSizes {
+    root: PathBuf,
+    ///How many levels below the root are included in the report
+    depth: usize,
+    dirs: HashMap<PathBuf, DirSize>,
+}
+
+///A directory as it shows up in the report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DirSizeRecord<'a> {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    pub path: &'a Path,
+    ///How many levels below the root this directory is
+    pub depth: usize,
+    #[serde(flatten)]
+    pub size: DirSize,
+}
+
+impl DirSizes {
+    pub fn new(root: PathBuf, depth: usize) -> Self {
+        let mut dirs = HashMap::new();
+        dirs.insert(root.clone(), DirSize::default());
+        Self { root, depth, dirs }
+    }
+
+    ///Size info for `dir`, `None` if it was not found
+    pub fn get(&self, dir: &Path) -> Option<&DirSize> {
+        self.dirs.get(dir)
+    }
+
+    ///Records a file of `bytes` size in the directory containing `path`
+    pub fn add_file(&mut self, path: &Path, bytes: u64) {
+        if let Some(parent) = path.parent() {
+            let parent = self.dirs.entry(parent.to_path_buf()).or_default();
+            parent.bytes += bytes;
+            parent.files += 1;
+        }
+    }
+
+    ///Records the directory at `path` in its parent
+    pub fn add_dir(&mut self, path: &Path) {
+        self.dirs.entry(path.to_path_buf()).or_default();
+        if let Some(parent) = path.parent() {
+            self.dirs.entry(parent.to_path_buf()).or_default().dirs += 1;
+        }
+    }
+
+    ///Adds the sizes gathered by another thread, must be called before `roll_up`
+    pub fn merge(&mut self, other: DirSizes) {
+        for (path, size) in other.dirs {
+            self.dirs.entry(path).or_default().add(&size);
+        }
+    }
+
+    ///Adds the totals of every directory to all of its ancestors up to the root
+    pub fn roll_up(&mut self) {
+        let mut paths: Vec<_> = self.dirs.keys().cloned().collect();
+        //Deepest first so every directory is complete before it is added to its parent
+        paths.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+        for path in paths {
+            if path == self.root {
+                continue;
+            }
+            let size = self.dirs[&path];
+            if let Some(parent) = path.parent().and_then(|p| self.dirs.get_mut(p)) {
+                parent.add(&size);
+            }
+        }
+    }
+
+    ///The directories down to `depth` levels below the root, in tree order with the
+    ///children of each directory sorted by size, largest first
+    pub fn report(&self) -> Vec<DirSizeRecord<'_>> {
+        let root_depth = self.root.components().count();
+        let mut children: HashMap<&Path, Vec<(&Path, &DirSize)>> = HashMap::new();
+        for (path, size) in &self.dirs {
+            let depth = path.components().count().saturating_sub(root_depth);
+            if *path == self.root || depth > self.depth {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                children.entry(parent).or_default().push((path, size));
+            }
+        }
+        for c in children.values_mut() {
+            c.sort_by(|(a_path, a), (b_path, b)| b.bytes.cmp(&a.bytes).then(a_path.cmp(b_path)));
+        }
+
+        let mut out = Vec::new();
+        let mut stack = vec![(self.root.as_path(), 0)];
+        while let Some((path, depth)) = stack.pop() {
+            out.push(DirSizeRecord {
+                path,
+                depth,
+                size: self.dirs.get(path).copied().unwrap_or_default(),
+            });
+            if let Some(c) = children.get(path) {
+                stack.extend(c.iter().rev().map(|(p, _)| (*p, depth + 1)));
+            }
+        }
+        out
+    }
+}
+
+impl std::fmt::Display for DirSizes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for DirSizes {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(f, "\nDirectory sizes:")?;
+        for record in self.report() {
+            write!(
+                f,
+                "\n{:indent$}{}: {}, {} files, {} dirs",
+                "",
+                record.path.to_string_lossy(),
+                format_size(record.size.bytes, units),
+                record.size.files,
+                record.size.dirs,
+                indent = 2 + record.depth * 2
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for DirSizes {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let report = self.report();
+        let mut seq = s.serialize_seq(Some(report.len()))?;
+        for record in report {
+            seq.serialize_element(&record)?;
+        }
+        seq.end()
+    }
+}
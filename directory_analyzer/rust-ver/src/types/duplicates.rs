@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use super::{format_size, serialize_paths_lossy, DisplaySizes, SizeUnits};
+
+///A group of paths that all have the same content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateSet {
+    ///Size of each file in the set
+    size: u64,
+    ///Bytes that could be freed by keeping only one copy
+    wasted_bytes: u64,
+    #[serde(serialize_with = "serialize_paths_lossy")]
+    paths: Vec<PathBuf>,
+}
+
+impl DuplicateSet {
+    ///Paths are sorted so the set is the same no matter the order the files were found in
+    pub fn new(size: u64, wasted_bytes: u64, mut paths: Vec<PathBuf>) -> Self {
+        paths.sort();
+        Self {
+            size,
+            wasted_bytes,
+            paths,
+        }
+    }
+
+    ///Size of each file in the set
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    ///Bytes that could be freed by keeping only one copy
+    pub fn wasted_bytes(&self) -> u64 {
+        self.wasted_bytes
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+///Info about files with the same content
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct DuplicateInfo {
+    ///Every file that could have a duplicate along with its size, gathered while walking
+    #[serde(skip)]
+    candidates: Vec<(PathBuf, u64)>,
+    ///Sets of different files with the same content, most wasted bytes first
+    duplicate_sets: Vec<DuplicateSet>,
+    ///Sets of paths that are hard links to the same file, these take up no extra space
+    hard_link_sets: Vec<DuplicateSet>,
+    ///Total bytes that could be freed by removing duplicates
+    wasted_bytes: u64,
+}
+
+impl DuplicateInfo {
+    ///Records a file to be checked for duplicates once walking is done
+    pub fn add_candidate(&mut self, path: PathBuf, size: u64) {
+        self.candidates.push((path, size));
+    }
+
+    ///Takes the files gathered while walking, leaving none behind
+    pub fn take_candidates(&mut self) -> Vec<(PathBuf, u64)> {
+        std::mem::take(&mut self.candidates)
+    }
+
+    ///Adds the candidates found by another thread
+    pub fn merge(&mut self, other: DuplicateInfo) {
+        self.candidates.extend(other.candidates);
+    }
+
+    ///Stores the results, sorting the sets so the output doesn't depend on the order files were found in
+    pub fn set_results(
+        &mut self,
+        mut duplicate_sets: Vec<DuplicateSet>,
+        mut hard_link_sets: Vec<DuplicateSet>,
+    ) {
+        let order = |a: &DuplicateSet, b: &DuplicateSet| {
+            b.wasted_bytes
+                .cmp(&a.wasted_bytes)
+                .then(b.size.cmp(&a.size))
+                .then(a.paths.cmp(&b.paths))
+        };
+        duplicate_sets.sort_by(order);
+        hard_link_sets.sort_by(order);
+        self.wasted_bytes = duplicate_sets.iter().map(|s| s.wasted_bytes).sum();
+        self.duplicate_sets = duplicate_sets;
+        self.hard_link_sets = hard_link_sets;
+    }
+
+    ///Sets of different files with the same content, most wasted bytes first
+    pub fn duplicate_sets(&self) -> &[DuplicateSet] {
+        &self.duplicate_sets
+    }
+
+    ///Sets of paths that are hard links to the same file
+    pub fn hard_link_sets(&self) -> &[DuplicateSet] {
+        &self.hard_link_sets
+    }
+
+    ///Total bytes that could be freed by removing duplicates
+    pub fn wasted_bytes(&self) -> u64 {
+        self.wasted_bytes
+    }
+}
+
+impl std::fmt::Display for DuplicateInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for DuplicateInfo {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "\nDuplicate files: {} sets, {} wasted",
+            self.duplicate_sets.len(),
+            format_size(self.wasted_bytes, units)
+        )?;
+        for set in &self.duplicate_sets {
+            write!(
+                f,
+                "\n  {} copies of {}, {} wasted:",
+                set.paths.len(),
+                format_size(set.size, units),
+                format_size(set.wasted_bytes, units)
+            )?;
+            write_paths(f, &set.paths)?;
+        }
+        if !self.hard_link_sets.is_empty() {
+            write!(f, "\nHard links(not counted as wasted):")?;
+            for set in &self.hard_link_sets {
+                write!(
+                    f,
+                    "\n  {} links to the same {}:",
+                    set.paths.len(),
+                    format_size(set.size, units)
+                )?;
+                write_paths(f, &set.paths)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_paths(f: &mut std::fmt::Formatter<'_>, paths: &[PathBuf]) -> std::fmt::Result {
+    for path in paths {
+        write!(f, "\n    {}", path.to_string_lossy())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Serialize, Serializer};
+
+use super::serialize_path_lossy;
+
+///An entry that could not be analyzed, such as a directory that could not be read or a file
+///that was removed while walking
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryError {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    path: PathBuf,
+    ///The kind of error, e.g. PermissionDenied or NotFound
+    #[serde(serialize_with = "serialize_error_kind")]
+    kind: io::ErrorKind,
+    message: String,
+}
+
+impl EntryError {
+    pub fn new(path: PathBuf, kind: io::ErrorKind, message: String) -> Self {
+        Self {
+            path,
+            kind,
+            message,
+        }
+    }
+
+    ///The error that happened while analyzing `path`, errors that did not come from io are `Other`
+    pub fn from_error(path: PathBuf, err: &anyhow::Error) -> Self {
+        let kind = match err.downcast_ref::<io::Error>() {
+            Some(e) => e.kind(),
+            None => io::ErrorKind::Other,
+        };
+        Self::new(path, kind, err.to_string())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///The kind of error, e.g. PermissionDenied or NotFound
+    pub fn kind(&self) -> io::ErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for EntryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:?}, {}",
+            self.path.to_string_lossy(),
+            self.kind,
+            self.message
+        )
+    }
+}
+
+fn serialize_error_kind<S: Serializer>(kind: &io::ErrorKind, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("{kind:?}"))
+}
@@ -0,0 +1,122 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::bail;
+
+use super::{file_extension, Args, ExtensionNormalization, FileExtension};
+
+///Aliases used by `--normalize-extensions aliases`, from the alias to the extension it is
+///counted as
+const DEFAULT_ALIASES: [(&str, &str); 8] = [
+    ("jpeg", "jpg"),
+    ("jpe", "jpg"),
+    ("htm", "html"),
+    ("tif", "tiff"),
+    ("yml", "yaml"),
+    ("markdown", "md"),
+    ("mpeg", "mpg"),
+    ("tgz", "tar.gz"),
+];
+
+///Extensions made of more than one part, used by `--normalize-extensions compound`
+const COMPOUND_EXTENSIONS: [&str; 11] = [
+    "tar.gz", "tar.bz2", "tar.xz", "tar.zst", "tar.lz", "tar.lz4", "tar.lzma", "tar.z", "d.ts",
+    "d.mts", "d.cts",
+];
+
+///An extension that is counted as another one, e.g. `jpeg=jpg`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionAlias {
+    from: FileExtension,
+    to: FileExtension,
+}
+
+impl FromStr for ExtensionAlias {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((from, to)) = s.split_once('=') else {
+            bail!("\"{s}\" needs to be in the form FROM=TO, e.g. jpeg=jpg");
+        };
+        //A leading dot is allowed so `.jpeg=.jpg` works too
+        let (from, to) = (
+            from.trim().trim_start_matches('.'),
+            to.trim().trim_start_matches('.'),
+        );
+        if from.is_empty() || to.is_empty() {
+            bail!("\"{s}\" needs an extension on both sides of the =");
+        }
+        Ok(Self {
+            from: from.to_string(),
+            to: to.to_string(),
+        })
+    }
+}
+
+///Turns the path of a file into the extension it is grouped under in file_info, following
+///--normalize-extensions and --extension-alias
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionNormalizer {
+    fold_case: bool,
+    compound: bool,
+    ///From the alias to the extension it is counted as, lowercase if `fold_case` is set
+    aliases: HashMap<FileExtension, FileExtension>,
+}
+
+impl ExtensionNormalizer {
+    pub fn new(args: &Args) -> Self {
+        let parts = args.normalize_extensions();
+        let fold_case = parts.contains(&ExtensionNormalization::Case);
+        let fold = |ext: &str| match fold_case {
+            true => ext.to_lowercase(),
+            false => ext.to_string(),
+        };
+        let mut aliases = HashMap::new();
+        if parts.contains(&ExtensionNormalization::Aliases) {
+            for (from, to) in DEFAULT_ALIASES {
+                aliases.insert(from.to_string(), to.to_string());
+            }
+        }
+        //Given after the defaults so they can replace them
+        for alias in args.extension_aliases() {
+            aliases.insert(fold(&alias.from), fold(&alias.to));
+        }
+        Self {
+            fold_case,
+            compound: parts.contains(&ExtensionNormalization::Compound),
+            aliases,
+        }
+    }
+
+    ///The extension `path` is grouped under, "" if it has none
+    pub fn extension(&self, path: &Path) -> FileExtension {
+        let mut ext = match self.compound_extension(path) {
+            Some(ext) => ext,
+            None => file_extension(path),
+        };
+        if self.fold_case {
+            ext = ext.to_lowercase();
+        }
+        match self.aliases.get(&ext) {
+            Some(alias) => alias.clone(),
+            None => ext,
+        }
+    }
+
+    ///e.g. "tar.gz" for `backup.tar.gz`, in the case it was written in
+    fn compound_extension(&self, path: &Path) -> Option<FileExtension> {
+        if !self.compound {
+            return None;
+        }
+        let name = path.file_name()?.to_string_lossy();
+        let lower = name.to_ascii_lowercase();
+        COMPOUND_EXTENSIONS.iter().find_map(|compound| {
+            //The name needs something before the extension, `.tar.gz` on its own is a hidden file
+            let start = lower.len().checked_sub(compound.len() + 1)?;
+            let suffix = lower.get(start..)?.strip_prefix('.')?;
+            match start > 0 && suffix == *compound {
+                true => Some(name[start + 1..].to_string()),
+                false => None,
+            }
+        })
+    }
+}
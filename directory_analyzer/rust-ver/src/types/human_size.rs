@@ -0,0 +1,57 @@
+use std::fmt;
+
+use super::SizeUnits;
+
+///Formats `bytes` as e.g. "1.5 MiB" in `units`, or as "1572864 bytes" if there are none
+pub fn format_size(bytes: u64, units: Option<SizeUnits>) -> String {
+    let Some(units) = units else {
+        return format!("{bytes} bytes");
+    };
+    let (base, prefixes) = match units {
+        SizeUnits::Si => (1000.0, ["B", "kB", "MB", "GB", "TB", "PB", "EB"]),
+        SizeUnits::Iec => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+    };
+    let mut size = bytes as f64;
+    let mut i = 0;
+    while size >= base && i < prefixes.len() - 1 {
+        size /= base;
+        i += 1;
+    }
+    match i {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", prefixes[i]),
+    }
+}
+
+///Formats a change in size as e.g. "+1.5 MiB" or "-20 bytes"
+pub fn format_size_change(change: i64, units: Option<SizeUnits>) -> String {
+    let sign = if change < 0 { '-' } else { '+' };
+    format!("{sign}{}", format_size(change.unsigned_abs(), units))
+}
+
+///Like `Display` but with sizes shown in the units chosen by --human-readable.
+///
+///Types implementing this also implement `Display`, which shows sizes as plain bytes
+pub trait DisplaySizes {
+    fn fmt_sizes(&self, f: &mut fmt::Formatter<'_>, units: Option<SizeUnits>) -> fmt::Result;
+
+    ///Wraps self so it can be used with `format!` with sizes shown in `units`
+    fn with_units(&self, units: Option<SizeUnits>) -> WithUnits<'_, Self>
+    where
+        Self: Sized,
+    {
+        WithUnits { value: self, units }
+    }
+}
+
+///Displays the value it wraps with sizes in the given units, see `DisplaySizes::with_units`
+pub struct WithUnits<'a, T> {
+    value: &'a T,
+    units: Option<SizeUnits>,
+}
+
+impl<T: DisplaySizes> fmt::Display for WithUnits<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt_sizes(f, self.units)
+    }
+}
@@ -1,11 +1,15 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
 };
 
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
-use super::FileExtension;
+use super::{
+    format_size, AgeHistogram, CategoryInfo, DirSizes, DisplaySizes, DuplicateInfo, EntryError,
+    FileExtension, FileTimeRecord, LargestFiles, OwnerInfo, PermissionAudit, SizeBasis,
+    SizeHistogram, SizeUnits, SnapshotTotals, SortBy, SortOrder, TypeMismatch,
+};
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize)]
 pub struct AnalyzedInfo {
@@ -15,31 +19,186 @@ pub struct AnalyzedInfo {
     found_files: u32,
     ///Info about symlinks found during the analysis
     found_symlinks: Option<SymlinkInfo>,
+    ///Counts of entries that are not files, directories or symlinks
+    special_files: Option<SpecialFileInfo>,
     ///Info about files grouped by file type
+    #[serde(serialize_with = "serialize_sorted_file_info")]
     file_info: Option<HashMap<FileExtension, FileTypeInfo>>,
+    ///Count and bytes of files in each category such as image or video
+    categories: Option<CategoryInfo>,
+    ///What the file types are ordered by in the text output and exports
+    #[serde(skip)]
+    sort_by: SortBy,
+    #[serde(skip)]
+    sort_order: SortOrder,
     ///How many bytes taken up by all the files
     total_bytes: u64,
+    ///How many bytes all the files take up on disk
+    allocated_bytes: Option<u64>,
+    ///Which size of each file the percentages and largest/smallest files are based on
+    size_basis: SizeBasis,
+    ///Units sizes are shown in by the text output, `None` for plain bytes
+    #[serde(skip)]
+    size_units: Option<SizeUnits>,
+    ///Count of extra hard links to files that were already counted
+    hard_links: u32,
+    ///Bytes that would have been counted more than once if hard links were not tracked
+    bytes_saved_by_hardlinks: u64,
+    ///Sizes of each directory including everything below it
+    dir_sizes: Option<DirSizes>,
+    ///The largest files found
+    largest_files: Option<LargestFiles>,
+    ///Count and bytes of files in each age range
+    age_histogram: Option<AgeHistogram>,
+    ///Count and bytes of files in each size range
+    size_histogram: Option<SizeHistogram>,
+    ///Count and bytes of files owned by each user and group
+    owners: Option<OwnerInfo>,
+    ///Entries with risky or unusual permissions
+    permission_audit: Option<PermissionAudit>,
+    ///Files whose extension does not match their content
+    type_mismatches: Option<Vec<TypeMismatch>>,
+    ///Count of entries left out because they matched an ignore option
+    ignored_entries: Option<u32>,
+    ///Directories left out because they are on another filesystem
+    #[serde(serialize_with = "serialize_opt_paths_lossy")]
+    skipped_mount_points: Option<Vec<PathBuf>>,
+    ///Files with the same content
+    duplicates: Option<DuplicateInfo>,
+    ///Entries that could not be analyzed, always empty with --strict since the first one stops the analysis
+    errors: Vec<EntryError>,
+    ///Totals only kept to save a snapshot
+    #[serde(skip)]
+    snapshot_totals: Option<SnapshotTotals>,
 }
 
 impl std::fmt::Display for AnalyzedInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let symlinks_str = match self.found_symlinks {
+        let units = self.size_units;
+        let symlinks_str = match &self.found_symlinks {
             Some(sym) => format!("\n{sym}"),
             None => "".to_string(),
         };
-        let info_str = match &self.file_info {
+        let special_files_str = match self.special_files {
+            Some(special) => format!("\n{special}"),
+            None => "".to_string(),
+        };
+        let info_str = match self.sorted_file_info() {
             Some(info) => {
                 let mut out = String::new();
                 for (file_ext, ft_info) in info {
-                    out.push_str(&format!("\nFile extension:{file_ext}{ft_info}"));
+                    out.push_str(&format!(
+                        "\nFile extension:{file_ext}{}",
+                        ft_info.with_units(units)
+                    ));
+                }
+                out
+            }
+            None => "".to_string(),
+        };
+        let dir_sizes_str = match &self.dir_sizes {
+            Some(dir_sizes) => format!("{}", dir_sizes.with_units(units)),
+            None => "".to_string(),
+        };
+        let largest_files_str = match &self.largest_files {
+            Some(largest_files) => {
+                format!("\nLargest files:{}", largest_files.with_units(units))
+            }
+            None => "".to_string(),
+        };
+        let age_str = match &self.age_histogram {
+            Some(age) => format!(
+                "\nFiles by {} time:{}",
+                age.time().label(),
+                age.with_units(units)
+            ),
+            None => "".to_string(),
+        };
+        let size_str = match &self.size_histogram {
+            Some(sizes) => format!("\nFiles by size:{}", sizes.with_units(units)),
+            None => "".to_string(),
+        };
+        let owners_str = match &self.owners {
+            Some(owners) => format!("{}", owners.with_units(units)),
+            None => "".to_string(),
+        };
+        let permission_audit_str = match &self.permission_audit {
+            Some(audit) => format!("{audit}"),
+            None => "".to_string(),
+        };
+        let type_mismatches_str = match &self.type_mismatches {
+            Some(mismatches) => {
+                let mut out = format!(
+                    "\nFiles whose extension does not match their content: {}",
+                    mismatches.len()
+                );
+                for mismatch in mismatches {
+                    out.push_str(&format!("\n  {mismatch}"));
                 }
                 out
             }
             None => "".to_string(),
         };
+        let categories_str = match &self.categories {
+            Some(categories) => format!("{}", categories.with_units(units)),
+            None => "".to_string(),
+        };
+        let duplicates_str = match &self.duplicates {
+            Some(duplicates) => format!("{}", duplicates.with_units(units)),
+            None => "".to_string(),
+        };
+        let ignored_str = match self.ignored_entries {
+            Some(ignored) => format!("\nIgnored entries: {ignored}"),
+            None => "".to_string(),
+        };
+        let allocated_str = match self.allocated_bytes {
+            Some(allocated) => match units {
+                Some(_) => format!("\nAllocated size: {}", format_size(allocated, units)),
+                None => format!("\nAllocated bytes: {allocated} bytes"),
+            },
+            None => "".to_string(),
+        };
+        let size_basis_str = match self.size_basis {
+            SizeBasis::Apparent => "",
+            SizeBasis::Allocated => {
+                "\nPercentages and largest/smallest files are by allocated bytes"
+            }
+        };
+        let hard_links_str = match self.hard_links {
+            0 => "".to_string(),
+            hard_links => format!(
+                "\nHard links: {hard_links}, {} only counted once",
+                format_size(self.bytes_saved_by_hardlinks, units)
+            ),
+        };
+        let mount_points_str = match &self.skipped_mount_points {
+            Some(mount_points) => {
+                let mut out = format!("\nSkipped mount points: {}", mount_points.len());
+                for path in mount_points {
+                    out.push_str(&format!("\n  {}", path.to_string_lossy()));
+                }
+                out
+            }
+            None => "".to_string(),
+        };
+        let errors_str = match self.errors.len() {
+            0 => "".to_string(),
+            len => {
+                let mut out = format!("\nErrors: {len}");
+                for error in &self.errors {
+                    out.push_str(&format!("\n  {error}"));
+                }
+                out
+            }
+        };
+        let total_str = match units {
+            Some(_) => format!("Total size: {}", format_size(self.total_bytes, units)),
+            None => format!("Total bytes: {} bytes", self.total_bytes),
+        };
         let str = format!(
-            "Found directories: {}\nFound files: {}\nTotal bytes: {} bytes{symlinks_str}\n{info_str}",
-            self.found_dirs, self.found_files, self.total_bytes
+            "Found directories: {}\nFound files: {}\n{total_str}{allocated_str}{size_basis_str}{hard_links_str}{ignored_str}{mount_points_str}{symlinks_str}{special_files_str}{categories_str}\n{info_str}{dir_sizes_str}{largest_files_str}{age_str}{size_str}{owners_str}{permission_audit_str}{type_mismatches_str}{duplicates_str}{errors_str}",
+            self.found_dirs,
+            self.found_files,
         );
         write!(f, "{str}")
     }
@@ -57,8 +216,29 @@ impl AnalyzedInfo {
             found_dirs,
             found_files,
             found_symlinks,
+            special_files: None,
             file_info,
             total_bytes,
+            allocated_bytes: None,
+            size_basis: SizeBasis::default(),
+            size_units: None,
+            sort_by: SortBy::default(),
+            sort_order: SortOrder::default(),
+            hard_links: 0,
+            bytes_saved_by_hardlinks: 0,
+            dir_sizes: None,
+            largest_files: None,
+            age_histogram: None,
+            size_histogram: None,
+            categories: None,
+            owners: None,
+            permission_audit: None,
+            type_mismatches: None,
+            ignored_entries: None,
+            skipped_mount_points: None,
+            duplicates: None,
+            errors: Vec::new(),
+            snapshot_totals: None,
         }
     }
 
@@ -75,6 +255,37 @@ impl AnalyzedInfo {
         self.file_info.as_ref()
     }
 
+    ///File types ordered by `sort_by` and `sort_order`.
+    ///
+    ///Ties are broken by name so the order is the same on every run
+    pub fn sorted_file_info(&self) -> Option<Vec<(&FileExtension, &FileTypeInfo)>> {
+        let size = |info: &FileTypeInfo| match self.size_basis {
+            SizeBasis::Apparent => info.size_in_bytes(),
+            SizeBasis::Allocated => info.allocated_bytes().unwrap_or_default(),
+        };
+        let mut out: Vec<_> = self.file_info.as_ref()?.iter().collect();
+        out.sort_by(|(a_ext, a), (b_ext, b)| {
+            let order = match self.sort_by {
+                SortBy::Size => size(a).cmp(&size(b)),
+                SortBy::Count => a.num_files().cmp(&b.num_files()),
+                SortBy::Name => a_ext.cmp(b_ext),
+                SortBy::Largest => a.largest_file().size().cmp(&b.largest_file().size()),
+            };
+            let order = match self.sort_order {
+                SortOrder::Asc => order,
+                SortOrder::Desc => order.reverse(),
+            };
+            order.then_with(|| a_ext.cmp(b_ext))
+        });
+        Some(out)
+    }
+
+    ///What the file types are ordered by in the text output and exports
+    pub fn set_sort(&mut self, sort_by: SortBy, sort_order: SortOrder) {
+        self.sort_by = sort_by;
+        self.sort_order = sort_order;
+    }
+
     pub fn found_symlinks(&self) -> Option<&SymlinkInfo> {
         self.found_symlinks.as_ref()
     }
@@ -103,6 +314,20 @@ impl AnalyzedInfo {
         None
     }
 
+    ///Counts of entries that are not files, directories or symlinks
+    pub fn special_files(&self) -> Option<&SpecialFileInfo> {
+        self.special_files.as_ref()
+    }
+
+    pub fn set_special_files(&mut self, special_files: Option<SpecialFileInfo>) {
+        self.special_files = special_files;
+    }
+
+    ///Returns a mutable reference to special_files if it is Some()
+    pub fn special_files_mut(&mut self) -> Option<&mut SpecialFileInfo> {
+        self.special_files.as_mut()
+    }
+
     ///Returns a mutable reference to file_info if it is Some()
     pub fn file_info_mut(&mut self) -> Option<&mut HashMap<FileExtension, FileTypeInfo>> {
         if let Some(file_info) = &mut self.file_info {
@@ -112,10 +337,21 @@ impl AnalyzedInfo {
     }
 
     ///Calculates the the percent of total files and percent of total size on disk for each type of file,if file info is gathered.
+    ///
+    ///Sizes are compared using `size_basis`
     pub fn calculate_percentages_for_info(&mut self) {
         if let Some(map) = &mut self.file_info {
-            for info in map.values_mut() {
-                info.calculate_percentages(self.total_bytes, self.found_files);
+            match (self.size_basis, self.allocated_bytes) {
+                (SizeBasis::Allocated, Some(allocated_bytes)) => {
+                    for info in map.values_mut() {
+                        info.calculate_allocated_percentages(allocated_bytes, self.found_files);
+                    }
+                }
+                _ => {
+                    for info in map.values_mut() {
+                        info.calculate_percentages(self.total_bytes, self.found_files);
+                    }
+                }
             }
         }
     }
@@ -128,6 +364,318 @@ impl AnalyzedInfo {
     pub fn total_bytes_mut(&mut self) -> &mut u64 {
         &mut self.total_bytes
     }
+
+    ///How many bytes all the files take up on disk
+    pub fn allocated_bytes(&self) -> Option<u64> {
+        self.allocated_bytes
+    }
+
+    pub fn set_allocated_bytes(&mut self, allocated_bytes: Option<u64>) {
+        self.allocated_bytes = allocated_bytes;
+    }
+
+    ///Returns a mutable reference to allocated_bytes if it is Some()
+    pub fn allocated_bytes_mut(&mut self) -> Option<&mut u64> {
+        self.allocated_bytes.as_mut()
+    }
+
+    ///Count of extra hard links to files that were already counted
+    pub fn hard_links(&self) -> u32 {
+        self.hard_links
+    }
+
+    pub fn hard_links_mut(&mut self) -> &mut u32 {
+        &mut self.hard_links
+    }
+
+    ///Bytes that would have been counted more than once if hard links were not tracked
+    pub fn bytes_saved_by_hardlinks(&self) -> u64 {
+        self.bytes_saved_by_hardlinks
+    }
+
+    pub fn bytes_saved_by_hardlinks_mut(&mut self) -> &mut u64 {
+        &mut self.bytes_saved_by_hardlinks
+    }
+
+    ///Units sizes are shown in by the text output, `None` for plain bytes
+    pub fn size_units(&self) -> Option<SizeUnits> {
+        self.size_units
+    }
+
+    pub fn set_size_units(&mut self, size_units: Option<SizeUnits>) {
+        self.size_units = size_units;
+    }
+
+    ///Which size of each file the percentages and largest/smallest files are based on
+    pub fn size_basis(&self) -> SizeBasis {
+        self.size_basis
+    }
+
+    pub fn set_size_basis(&mut self, size_basis: SizeBasis) {
+        self.size_basis = size_basis;
+    }
+
+    ///Sizes of each directory including everything below it
+    pub fn dir_sizes(&self) -> Option<&DirSizes> {
+        self.dir_sizes.as_ref()
+    }
+
+    pub fn set_dir_sizes(&mut self, dir_sizes: Option<DirSizes>) {
+        self.dir_sizes = dir_sizes;
+    }
+
+    ///Returns a mutable reference to dir_sizes if it is Some()
+    pub fn dir_sizes_mut(&mut self) -> Option<&mut DirSizes> {
+        self.dir_sizes.as_mut()
+    }
+
+    ///The largest files found
+    pub fn largest_files(&self) -> Option<&LargestFiles> {
+        self.largest_files.as_ref()
+    }
+
+    pub fn set_largest_files(&mut self, largest_files: Option<LargestFiles>) {
+        self.largest_files = largest_files;
+    }
+
+    ///Returns a mutable reference to largest_files if it is Some()
+    pub fn largest_files_mut(&mut self) -> Option<&mut LargestFiles> {
+        self.largest_files.as_mut()
+    }
+
+    ///Count and bytes of files in each age range
+    pub fn age_histogram(&self) -> Option<&AgeHistogram> {
+        self.age_histogram.as_ref()
+    }
+
+    pub fn set_age_histogram(&mut self, age_histogram: Option<AgeHistogram>) {
+        self.age_histogram = age_histogram;
+    }
+
+    ///Returns a mutable reference to age_histogram if it is Some()
+    pub fn age_histogram_mut(&mut self) -> Option<&mut AgeHistogram> {
+        self.age_histogram.as_mut()
+    }
+
+    ///Count and bytes of files in each size range
+    pub fn size_histogram(&self) -> Option<&SizeHistogram> {
+        self.size_histogram.as_ref()
+    }
+
+    pub fn set_size_histogram(&mut self, size_histogram: Option<SizeHistogram>) {
+        self.size_histogram = size_histogram;
+    }
+
+    ///Returns a mutable reference to size_histogram if it is Some()
+    pub fn size_histogram_mut(&mut self) -> Option<&mut SizeHistogram> {
+        self.size_histogram.as_mut()
+    }
+
+    ///Calculates the mean and median of each size histogram once all files are found
+    pub fn finish_size_histograms(&mut self) {
+        if let Some(sizes) = &mut self.size_histogram {
+            sizes.finish();
+        }
+        if let Some(map) = &mut self.file_info {
+            for sizes in map
+                .values_mut()
+                .filter_map(|info| info.size_histogram.as_mut())
+            {
+                sizes.finish();
+            }
+        }
+    }
+
+    ///Count and bytes of files in each category such as image or video
+    pub fn categories(&self) -> Option<&CategoryInfo> {
+        self.categories.as_ref()
+    }
+
+    pub fn set_categories(&mut self, categories: Option<CategoryInfo>) {
+        self.categories = categories;
+    }
+
+    ///Returns a mutable reference to categories if it is Some()
+    pub fn categories_mut(&mut self) -> Option<&mut CategoryInfo> {
+        self.categories.as_mut()
+    }
+
+    ///Count and bytes of files owned by each user and group
+    pub fn owners(&self) -> Option<&OwnerInfo> {
+        self.owners.as_ref()
+    }
+
+    pub fn set_owners(&mut self, owners: Option<OwnerInfo>) {
+        self.owners = owners;
+    }
+
+    ///Returns a mutable reference to owners if it is Some()
+    pub fn owners_mut(&mut self) -> Option<&mut OwnerInfo> {
+        self.owners.as_mut()
+    }
+
+    ///Entries with risky or unusual permissions
+    pub fn permission_audit(&self) -> Option<&PermissionAudit> {
+        self.permission_audit.as_ref()
+    }
+
+    pub fn set_permission_audit(&mut self, permission_audit: Option<PermissionAudit>) {
+        self.permission_audit = permission_audit;
+    }
+
+    ///Returns a mutable reference to permission_audit if it is Some()
+    pub fn permission_audit_mut(&mut self) -> Option<&mut PermissionAudit> {
+        self.permission_audit.as_mut()
+    }
+
+    ///Files whose extension does not match their content
+    pub fn type_mismatches(&self) -> Option<&[TypeMismatch]> {
+        self.type_mismatches.as_deref()
+    }
+
+    pub fn set_type_mismatches(&mut self, type_mismatches: Option<Vec<TypeMismatch>>) {
+        self.type_mismatches = type_mismatches;
+    }
+
+    ///Returns a mutable reference to type_mismatches if it is Some()
+    pub fn type_mismatches_mut(&mut self) -> Option<&mut Vec<TypeMismatch>> {
+        self.type_mismatches.as_mut()
+    }
+
+    ///Count of entries left out because they matched an ignore option
+    pub fn ignored_entries(&self) -> Option<u32> {
+        self.ignored_entries
+    }
+
+    pub fn set_ignored_entries(&mut self, ignored_entries: Option<u32>) {
+        self.ignored_entries = ignored_entries;
+    }
+
+    ///Returns a mutable reference to ignored_entries if it is Some()
+    pub fn ignored_entries_mut(&mut self) -> Option<&mut u32> {
+        self.ignored_entries.as_mut()
+    }
+
+    ///Directories left out because they are on another filesystem
+    pub fn skipped_mount_points(&self) -> Option<&[PathBuf]> {
+        self.skipped_mount_points.as_deref()
+    }
+
+    pub fn set_skipped_mount_points(&mut self, skipped_mount_points: Option<Vec<PathBuf>>) {
+        self.skipped_mount_points = skipped_mount_points;
+    }
+
+    ///Returns a mutable reference to skipped_mount_points if it is Some()
+    pub fn skipped_mount_points_mut(&mut self) -> Option<&mut Vec<PathBuf>> {
+        self.skipped_mount_points.as_mut()
+    }
+
+    ///Files with the same content
+    pub fn duplicates(&self) -> Option<&DuplicateInfo> {
+        self.duplicates.as_ref()
+    }
+
+    pub fn set_duplicates(&mut self, duplicates: Option<DuplicateInfo>) {
+        self.duplicates = duplicates;
+    }
+
+    ///Returns a mutable reference to duplicates if it is Some()
+    pub fn duplicates_mut(&mut self) -> Option<&mut DuplicateInfo> {
+        self.duplicates.as_mut()
+    }
+
+    ///Entries that could not be analyzed
+    pub fn errors(&self) -> &[EntryError] {
+        &self.errors
+    }
+
+    pub fn errors_mut(&mut self) -> &mut Vec<EntryError> {
+        &mut self.errors
+    }
+
+    ///Totals only kept to save a snapshot
+    pub fn snapshot_totals(&self) -> Option<&SnapshotTotals> {
+        self.snapshot_totals.as_ref()
+    }
+
+    pub fn set_snapshot_totals(&mut self, snapshot_totals: Option<SnapshotTotals>) {
+        self.snapshot_totals = snapshot_totals;
+    }
+
+    ///Returns a mutable reference to snapshot_totals if it is Some()
+    pub fn snapshot_totals_mut(&mut self) -> Option<&mut SnapshotTotals> {
+        self.snapshot_totals.as_mut()
+    }
+
+    ///Adds the info gathered by another thread to this one.
+    ///
+    ///Percentages are not merged, they should be calculated after all merging is done
+    pub fn merge(&mut self, other: AnalyzedInfo) {
+        self.found_dirs += other.found_dirs;
+        self.found_files += other.found_files;
+        self.total_bytes += other.total_bytes;
+        self.errors.extend(other.errors);
+        if let (Some(totals), Some(other)) = (&mut self.snapshot_totals, other.snapshot_totals) {
+            totals.merge(other);
+        }
+        self.hard_links += other.hard_links;
+        self.bytes_saved_by_hardlinks += other.bytes_saved_by_hardlinks;
+        if let (Some(allocated), Some(other)) = (&mut self.allocated_bytes, other.allocated_bytes) {
+            *allocated += other;
+        }
+        if let (Some(ignored), Some(other)) = (&mut self.ignored_entries, other.ignored_entries) {
+            *ignored += other;
+        }
+        if let (Some(mount_points), Some(other)) =
+            (&mut self.skipped_mount_points, other.skipped_mount_points)
+        {
+            mount_points.extend(other);
+        }
+        if let (Some(sym), Some(other)) = (&mut self.found_symlinks, other.found_symlinks) {
+            sym.merge(&other);
+        }
+        if let (Some(special), Some(other)) = (&mut self.special_files, other.special_files) {
+            special.merge(&other);
+        }
+        if let (Some(map), Some(other)) = (&mut self.file_info, other.file_info) {
+            for (ext, info) in other {
+                match map.get_mut(&ext) {
+                    Some(t) => t.merge(info),
+                    None => {
+                        map.insert(ext, info);
+                    }
+                }
+            }
+        }
+        if let (Some(dir_sizes), Some(other)) = (&mut self.dir_sizes, other.dir_sizes) {
+            dir_sizes.merge(other);
+        }
+        if let (Some(largest_files), Some(other)) = (&mut self.largest_files, other.largest_files) {
+            largest_files.merge(other);
+        }
+        if let (Some(age), Some(other)) = (&mut self.age_histogram, other.age_histogram) {
+            age.merge(&other);
+        }
+        if let (Some(sizes), Some(other)) = (&mut self.size_histogram, other.size_histogram) {
+            sizes.merge(other);
+        }
+        if let (Some(categories), Some(other)) = (&mut self.categories, other.categories) {
+            categories.merge(other);
+        }
+        if let (Some(owners), Some(other)) = (&mut self.owners, other.owners) {
+            owners.merge(other);
+        }
+        if let (Some(audit), Some(other)) = (&mut self.permission_audit, other.permission_audit) {
+            audit.merge(other);
+        }
+        if let (Some(mismatches), Some(other)) = (&mut self.type_mismatches, other.type_mismatches)
+        {
+            mismatches.extend(other);
+        }
+        if let (Some(duplicates), Some(other)) = (&mut self.duplicates, other.duplicates) {
+            duplicates.merge(other);
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Default)]
@@ -135,29 +683,111 @@ pub struct FileTypeInfo {
     num_files: u32,
     largest_file: FileTypeInfoRecords,
     smallest_file: FileTypeInfoRecords,
+    ///The file with the oldest time used by --age
+    oldest_file: Option<FileTimeRecord>,
+    ///The file with the newest time used by --age
+    newest_file: Option<FileTimeRecord>,
     ///Total size of all files of this type
     size_in_bytes: u64,
+    ///Total space all files of this type take up on disk
+    allocated_bytes: Option<u64>,
     percent_of_total_files: Option<f32>,
     percent_of_total_size: Option<f32>,
+    ///The largest files of this type
+    largest_files: Option<LargestFiles>,
+    ///Count and bytes of files of this type in each age range
+    age_histogram: Option<AgeHistogram>,
+    ///Count and bytes of files of this type in each size range
+    size_histogram: Option<SizeHistogram>,
 }
 
 impl std::fmt::Display for FileTypeInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for FileTypeInfo {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
         let (per_tot_files, per_tot_size) = self.percentages_in_string();
         let per_tot_files = format!(" % of total files: {per_tot_files}\n");
         let per_tot_size = format!("\n  % of total size: {per_tot_size}");
+        let largest_files = match &self.largest_files {
+            Some(largest_files) => {
+                format!("\n  Largest files:{}", largest_files.with_units(units))
+            }
+            None => "".to_string(),
+        };
+        let allocated = match self.allocated_bytes {
+            Some(allocated) => match units {
+                Some(_) => format!(
+                    "\n  Allocated size for this type: {}",
+                    format_size(allocated, units)
+                ),
+                None => format!("\n  Allocated bytes for this type: {allocated}"),
+            },
+            None => "".to_string(),
+        };
+        let age_records = match (&self.oldest_file, &self.newest_file) {
+            (Some(oldest), Some(newest)) => {
+                format!("\n  Oldest file: {oldest}\n  Newest file: {newest}")
+            }
+            _ => "".to_string(),
+        };
+        let age_histogram = match &self.age_histogram {
+            Some(histogram) => format!(
+                "\n  Files by {} time:{}",
+                histogram.time().label(),
+                histogram.with_units(units)
+            ),
+            None => "".to_string(),
+        };
+        let size_histogram = match &self.size_histogram {
+            Some(sizes) => format!("\n  Files by size:{}", sizes.with_units(units)),
+            None => "".to_string(),
+        };
+        let size = match units {
+            Some(_) => format!(
+                "Size for this type: {}",
+                format_size(self.size_in_bytes, units)
+            ),
+            None => format!("Size in bytes for this type: {}", self.size_in_bytes),
+        };
         write!(
             f,
-            "\n  Number of files:{}\n{}  Largest file: {}\n  Smallest file: {}\n  Size in bytes for this type: {}{}",
-            self.num_files,per_tot_files, self.largest_file, self.smallest_file, self.size_in_bytes,per_tot_size
+            "\n  Number of files:{}\n{}  Largest file: {}\n  Smallest file: {}{}\n  {}{}{}{}{}{}",
+            self.num_files,
+            per_tot_files,
+            self.largest_file.with_units(units),
+            self.smallest_file.with_units(units),
+            age_records,
+            size,
+            allocated,
+            per_tot_size,
+            largest_files,
+            age_histogram,
+            size_histogram
         )
     }
 }
 
+///The file type of `path`, "" if it has no extension
+pub fn file_extension(path: &Path) -> FileExtension {
+    match path.extension() {
+        Some(ext) => ext.to_string_lossy().to_string(),
+        None => "".to_string(),
+    }
+}
+
 ///This is for holding info about specific notable files
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct FileTypeInfoRecords {
     ///Path to the path
+    #[serde(serialize_with = "serialize_path_lossy")]
     path: PathBuf,
     ///The size of the file
     size: u64,
@@ -165,27 +795,55 @@ pub struct FileTypeInfoRecords {
 
 impl std::fmt::Display for FileTypeInfoRecords {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for FileTypeInfoRecords {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
         write!(
             f,
             "\n    Path:{}\n    Size: {}",
             self.path.to_string_lossy(),
-            self.size
+            match units {
+                Some(_) => format_size(self.size, units),
+                None => self.size.to_string(),
+            }
         )
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, Copy, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct SymlinkInfo {
     found_symlinks: u32,
     ///Number of symlinks that point to files
     file_symlinks: u32,
     ///Number of symlinks that point to directories
     dir_symlinks: u32,
+    ///Number of symlinks that point to special files, such as sockets, FIFOs and devices
+    special_symlinks: u32,
+    ///Number of symlinks whose target can't be followed, e.g. it doesn't exist or loops
+    broken_symlinks: u32,
+    ///Symlinks whose target can't be followed, sorted by path
+    broken: Vec<BrokenSymlink>,
 }
 
 impl std::fmt::Display for SymlinkInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f,"\nFound symbolic links:{}\nSymlinks that point to files: {}\nSymlinks that point to directories: {}",self.found_symlinks,self.file_symlinks,self.dir_symlinks)
+        write!(f,"\nFound symbolic links:{}\nSymlinks that point to files: {}\nSymlinks that point to directories: {}",self.found_symlinks,self.file_symlinks,self.dir_symlinks)?;
+        write!(
+            f,
+            "\nSymlinks that point to special files: {}\nBroken symlinks: {}",
+            self.special_symlinks, self.broken_symlinks
+        )?;
+        for broken in &self.broken {
+            write!(f, "\n  {broken}")?;
+        }
+        Ok(())
     }
 }
 
@@ -195,6 +853,9 @@ impl SymlinkInfo {
             found_symlinks,
             file_symlinks,
             dir_symlinks,
+            special_symlinks: 0,
+            broken_symlinks: 0,
+            broken: Vec::new(),
         }
     }
 
@@ -212,6 +873,21 @@ impl SymlinkInfo {
         self.dir_symlinks
     }
 
+    ///Number of symlinks that point to special files, such as sockets, FIFOs and devices
+    pub fn special_symlinks(&self) -> u32 {
+        self.special_symlinks
+    }
+
+    ///Number of symlinks whose target can't be followed, e.g. it doesn't exist or loops
+    pub fn broken_symlinks(&self) -> u32 {
+        self.broken_symlinks
+    }
+
+    ///Symlinks that point to something that doesn't exist
+    pub fn broken(&self) -> &[BrokenSymlink] {
+        &self.broken
+    }
+
     pub fn found_symlinks_mut(&mut self) -> &mut u32 {
         &mut self.found_symlinks
     }
@@ -223,6 +899,141 @@ impl SymlinkInfo {
     pub fn dir_symlinks_mut(&mut self) -> &mut u32 {
         &mut self.dir_symlinks
     }
+
+    pub fn special_symlinks_mut(&mut self) -> &mut u32 {
+        &mut self.special_symlinks
+    }
+
+    ///Counts a symlink that points to something that doesn't exist
+    pub fn add_broken(&mut self, broken: BrokenSymlink) {
+        self.found_symlinks += 1;
+        self.broken_symlinks += 1;
+        self.broken.push(broken);
+    }
+
+    ///Sorts the broken symlinks so they don't depend on the order they were found in
+    pub fn sort(&mut self) {
+        self.broken.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    pub fn merge(&mut self, other: &SymlinkInfo) {
+        self.found_symlinks += other.found_symlinks;
+        self.file_symlinks += other.file_symlinks;
+        self.dir_symlinks += other.dir_symlinks;
+        self.special_symlinks += other.special_symlinks;
+        self.broken_symlinks += other.broken_symlinks;
+        self.broken.extend(other.broken.iter().cloned());
+    }
+}
+
+///Counts of entries that are not files, directories or symlinks, these only exist on Unix
+#[derive(Debug, Clone, PartialEq, Eq, Default, Copy, Serialize)]
+pub struct SpecialFileInfo {
+    ///Named pipes
+    fifos: u32,
+    sockets: u32,
+    block_devices: u32,
+    character_devices: u32,
+}
+
+impl std::fmt::Display for SpecialFileInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "\nFIFOs: {}\nSockets: {}\nBlock devices: {}\nCharacter devices: {}",
+            self.fifos, self.sockets, self.block_devices, self.character_devices
+        )
+    }
+}
+
+impl SpecialFileInfo {
+    pub fn new(fifos: u32, sockets: u32, block_devices: u32, character_devices: u32) -> Self {
+        Self {
+            fifos,
+            sockets,
+            block_devices,
+            character_devices,
+        }
+    }
+
+    ///Named pipes
+    pub fn fifos(&self) -> u32 {
+        self.fifos
+    }
+
+    pub fn sockets(&self) -> u32 {
+        self.sockets
+    }
+
+    pub fn block_devices(&self) -> u32 {
+        self.block_devices
+    }
+
+    pub fn character_devices(&self) -> u32 {
+        self.character_devices
+    }
+
+    ///Counts an entry of type `file_type`, files, directories and symlinks are not counted
+    pub fn add(&mut self, file_type: std::fs::FileType) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_fifo() {
+                self.fifos += 1;
+            } else if file_type.is_socket() {
+                self.sockets += 1;
+            } else if file_type.is_block_device() {
+                self.block_devices += 1;
+            } else if file_type.is_char_device() {
+                self.character_devices += 1;
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = file_type;
+    }
+
+    pub fn merge(&mut self, other: &SpecialFileInfo) {
+        self.fifos += other.fifos;
+        self.sockets += other.sockets;
+        self.block_devices += other.block_devices;
+        self.character_devices += other.character_devices;
+    }
+}
+
+///A symlink whose target can't be followed, e.g. it doesn't exist or loops
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BrokenSymlink {
+    #[serde(serialize_with = "serialize_path_lossy")]
+    path: PathBuf,
+    ///Where the symlink points, as it is stored in the symlink
+    #[serde(serialize_with = "serialize_path_lossy")]
+    target: PathBuf,
+}
+
+impl BrokenSymlink {
+    pub fn new(path: PathBuf, target: PathBuf) -> Self {
+        Self { path, target }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///Where the symlink points, as it is stored in the symlink
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+impl std::fmt::Display for BrokenSymlink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {}",
+            self.path.to_string_lossy(),
+            self.target.to_string_lossy()
+        )
+    }
 }
 
 impl FileTypeInfoRecords {
@@ -238,6 +1049,20 @@ impl FileTypeInfoRecords {
     pub fn size(&self) -> u64 {
         self.size
     }
+
+    ///If this record should replace `other` as the largest file.
+    ///
+    ///Ties are broken by path so the result does not depend on the order files are found in
+    pub fn is_larger_than(&self, other: &FileTypeInfoRecords) -> bool {
+        self.size > other.size || (self.size == other.size && self.path < other.path)
+    }
+
+    ///If this record should replace `other` as the smallest file.
+    ///
+    ///Ties are broken by path so the result does not depend on the order files are found in
+    pub fn is_smaller_than(&self, other: &FileTypeInfoRecords) -> bool {
+        self.size < other.size || (self.size == other.size && self.path < other.path)
+    }
 }
 
 impl FileTypeInfo {
@@ -252,8 +1077,14 @@ impl FileTypeInfo {
             largest_file,
             smallest_file,
             size_in_bytes,
+            allocated_bytes: None,
             percent_of_total_files: None,
             percent_of_total_size: None,
+            largest_files: None,
+            oldest_file: None,
+            newest_file: None,
+            age_histogram: None,
+            size_histogram: None,
         }
     }
 
@@ -281,6 +1112,20 @@ impl FileTypeInfo {
         &mut self.size_in_bytes
     }
 
+    ///Total space all files of this type take up on disk
+    pub fn allocated_bytes(&self) -> Option<u64> {
+        self.allocated_bytes
+    }
+
+    pub fn set_allocated_bytes(&mut self, allocated_bytes: Option<u64>) {
+        self.allocated_bytes = allocated_bytes;
+    }
+
+    ///Returns a mutable reference to allocated_bytes if it is Some()
+    pub fn allocated_bytes_mut(&mut self) -> Option<&mut u64> {
+        self.allocated_bytes.as_mut()
+    }
+
     pub fn set_largest_file(&mut self, largest_file: FileTypeInfoRecords) {
         self.largest_file = largest_file;
     }
@@ -289,33 +1134,151 @@ impl FileTypeInfo {
         self.smallest_file = smallest_file;
     }
 
+    ///The largest files of this type
+    pub fn largest_files(&self) -> Option<&LargestFiles> {
+        self.largest_files.as_ref()
+    }
+
+    pub fn set_largest_files(&mut self, largest_files: Option<LargestFiles>) {
+        self.largest_files = largest_files;
+    }
+
+    ///The file with the oldest time used by --age
+    pub fn oldest_file(&self) -> Option<&FileTimeRecord> {
+        self.oldest_file.as_ref()
+    }
+
+    ///The file with the newest time used by --age
+    pub fn newest_file(&self) -> Option<&FileTimeRecord> {
+        self.newest_file.as_ref()
+    }
+
+    ///Count and bytes of files of this type in each age range
+    pub fn age_histogram(&self) -> Option<&AgeHistogram> {
+        self.age_histogram.as_ref()
+    }
+
+    pub fn set_age_histogram(&mut self, age_histogram: Option<AgeHistogram>) {
+        self.age_histogram = age_histogram;
+    }
+
+    ///Count and bytes of files of this type in each size range
+    pub fn size_histogram(&self) -> Option<&SizeHistogram> {
+        self.size_histogram.as_ref()
+    }
+
+    pub fn set_size_histogram(&mut self, size_histogram: Option<SizeHistogram>) {
+        self.size_histogram = size_histogram;
+    }
+
+    ///Returns a mutable reference to size_histogram if it is Some()
+    pub fn size_histogram_mut(&mut self) -> Option<&mut SizeHistogram> {
+        self.size_histogram.as_mut()
+    }
+
+    ///Adds a file that is `age_secs` old to the age histogram and replaces the oldest and/or
+    ///newest file with `record` if it is a better fit. Does nothing if ages are not tracked
+    pub fn update_age(&mut self, record: FileTimeRecord, age_secs: u64, bytes: u64) {
+        let Some(histogram) = &mut self.age_histogram else {
+            return;
+        };
+        histogram.add(age_secs, bytes);
+        if self
+            .oldest_file
+            .as_ref()
+            .is_none_or(|oldest| record.is_older_than(oldest))
+        {
+            self.oldest_file = Some(record.clone());
+        }
+        if self
+            .newest_file
+            .as_ref()
+            .is_none_or(|newest| record.is_newer_than(newest))
+        {
+            self.newest_file = Some(record);
+        }
+    }
+
+    ///Replaces the largest and/or smallest file with `record` if it is a better fit
+    pub fn update_records(&mut self, record: FileTypeInfoRecords) {
+        if let Some(largest_files) = &mut self.largest_files {
+            largest_files.add(record.clone());
+        }
+        if record.is_larger_than(&self.largest_file) {
+            self.largest_file = record.clone();
+        }
+        if record.is_smaller_than(&self.smallest_file) {
+            self.smallest_file = record;
+        }
+    }
+
+    ///Adds the info about the same file type gathered by another thread to this one
+    pub fn merge(&mut self, other: FileTypeInfo) {
+        self.num_files += other.num_files;
+        self.size_in_bytes += other.size_in_bytes;
+        if let (Some(allocated), Some(other)) = (&mut self.allocated_bytes, other.allocated_bytes) {
+            *allocated += other;
+        }
+        if other.largest_file.is_larger_than(&self.largest_file) {
+            self.largest_file = other.largest_file;
+        }
+        if other.smallest_file.is_smaller_than(&self.smallest_file) {
+            self.smallest_file = other.smallest_file;
+        }
+        if let (Some(largest_files), Some(other)) = (&mut self.largest_files, other.largest_files) {
+            largest_files.merge(other);
+        }
+        if let Some(other) = other.oldest_file {
+            if self
+                .oldest_file
+                .as_ref()
+                .is_none_or(|o| other.is_older_than(o))
+            {
+                self.oldest_file = Some(other);
+            }
+        }
+        if let Some(other) = other.newest_file {
+            if self
+                .newest_file
+                .as_ref()
+                .is_none_or(|n| other.is_newer_than(n))
+            {
+                self.newest_file = Some(other);
+            }
+        }
+        if let (Some(age), Some(other)) = (&mut self.age_histogram, other.age_histogram) {
+            age.merge(&other);
+        }
+        if let (Some(sizes), Some(other)) = (&mut self.size_histogram, other.size_histogram) {
+            sizes.merge(other);
+        }
+    }
+
     ///Calculates `percent_of_total_files` and `percent_of_total_size`
     pub fn calculate_percentages(&mut self, total_bytes: u64, total_files: u32) {
         self.percent_of_total_files = Some(self.num_files as f32 / total_files as f32);
         self.percent_of_total_size = Some(self.size_in_bytes as f32 / total_bytes as f32);
     }
 
+    ///Calculates `percent_of_total_files` and `percent_of_total_size` using the allocated bytes,
+    ///falls back to the size if the allocated bytes were not tracked
+    pub fn calculate_allocated_percentages(
+        &mut self,
+        total_allocated_bytes: u64,
+        total_files: u32,
+    ) {
+        let allocated = self.allocated_bytes.unwrap_or(self.size_in_bytes);
+        self.percent_of_total_files = Some(self.num_files as f32 / total_files as f32);
+        self.percent_of_total_size = Some(allocated as f32 / total_allocated_bytes as f32);
+    }
+
     pub fn percentages_in_string(&self) -> (PercentageOfFiles, PercentageOfSize) {
         let per_tot_files = match self.percent_of_total_files {
-            Some(per) => {
-                let tmp = format!("{:.2}", per * 100.0);
-                if tmp == "0.00" {
-                    "< 0.01".to_string()
-                } else {
-                    tmp
-                }
-            }
+            Some(per) => format_percent(per),
             None => "N/A\n".to_string(),
         };
         let per_tot_size = match self.percent_of_total_size {
-            Some(per) => {
-                let tmp = format!("{:.2}", per * 100.0);
-                if tmp == "0.00" {
-                    "< 0.01".to_string()
-                } else {
-                    tmp
-                }
-            }
+            Some(per) => format_percent(per),
             None => "N/A".to_string(),
         };
         (per_tot_files, per_tot_size)
@@ -324,3 +1287,47 @@ impl FileTypeInfo {
 
 pub type PercentageOfFiles = String;
 pub type PercentageOfSize = String;
+
+///Formats a fraction as a percentage with two decimals, e.g. "12.50" for 0.125
+pub(crate) fn format_percent(fraction: f32) -> String {
+    let tmp = format!("{:.2}", fraction * 100.0);
+    if tmp == "0.00" {
+        "< 0.01".to_string()
+    } else {
+        tmp
+    }
+}
+
+///Serializes a path even if it is not valid UTF-8, which the default `Serialize` impl errors on
+pub(crate) fn serialize_path_lossy<S: Serializer>(path: &Path, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&path.to_string_lossy())
+}
+
+///Same as `serialize_path_lossy` for a list of paths
+pub(crate) fn serialize_paths_lossy<S: Serializer>(
+    paths: &[PathBuf],
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    s.collect_seq(paths.iter().map(|p| p.to_string_lossy()))
+}
+
+///Serializes the file types ordered by name so the JSON output is the same on every run
+fn serialize_sorted_file_info<S: Serializer>(
+    file_info: &Option<HashMap<FileExtension, FileTypeInfo>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    file_info
+        .as_ref()
+        .map(|map| map.iter().collect::<BTreeMap<_, _>>())
+        .serialize(s)
+}
+
+fn serialize_opt_paths_lossy<S: Serializer>(
+    paths: &Option<Vec<PathBuf>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    match paths {
+        Some(paths) => serialize_paths_lossy(paths, s),
+        None => s.serialize_none(),
+    }
+}
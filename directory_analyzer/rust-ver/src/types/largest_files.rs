@@ -0,0 +1,93 @@
+use std::{cmp::Reverse, collections::BinaryHeap, path::PathBuf};
+
+use serde::{Serialize, Serializer};
+
+use super::{format_size, DisplaySizes, FileTypeInfoRecords, SizeUnits};
+
+///Keeps the `n` largest files seen so far.
+///
+///Uses a min heap so only `n` files are held in memory no matter how many are added.
+///Ties are broken by path the same way as `FileTypeInfoRecords::is_larger_than`
+#[derive(Debug, Clone, Default)]
+pub struct LargestFiles {
+    n: usize,
+    heap: BinaryHeap<Reverse<(u64, Reverse<PathBuf>)>>,
+}
+
+impl LargestFiles {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            heap: BinaryHeap::with_capacity(n + 1),
+        }
+    }
+
+    ///How many files are kept
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn add(&mut self, record: FileTypeInfoRecords) {
+        let item = Reverse((record.size(), Reverse(record.path().to_path_buf())));
+        if self.heap.len() < self.n {
+            self.heap.push(item);
+        } else if self.heap.peek().is_some_and(|smallest| item < *smallest) {
+            self.heap.pop();
+            self.heap.push(item);
+        }
+    }
+
+    ///Adds the files kept by another thread
+    pub fn merge(&mut self, other: LargestFiles) {
+        for Reverse((size, Reverse(path))) in other.heap {
+            self.add(FileTypeInfoRecords::new(path, size));
+        }
+    }
+
+    ///The files kept, largest first
+    pub fn sorted(&self) -> Vec<FileTypeInfoRecords> {
+        let mut items: Vec<_> = self.heap.iter().map(|Reverse(item)| item).collect();
+        items.sort_by(|a, b| b.cmp(a));
+        items
+            .into_iter()
+            .map(|(size, Reverse(path))| FileTypeInfoRecords::new(path.clone(), *size))
+            .collect()
+    }
+}
+
+impl PartialEq for LargestFiles {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n && self.sorted() == other.sorted()
+    }
+}
+
+impl Serialize for LargestFiles {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.sorted().serialize(s)
+    }
+}
+
+impl std::fmt::Display for LargestFiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for LargestFiles {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        for (i, record) in self.sorted().iter().enumerate() {
+            write!(
+                f,
+                "\n    {}. {} {}",
+                i + 1,
+                format_size(record.size(), units),
+                record.path().to_string_lossy()
+            )?;
+        }
+        Ok(())
+    }
+}
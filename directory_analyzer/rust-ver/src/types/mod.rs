@@ -1,37 +1,39 @@
 pub type FileExtension = String;
 
-///Allows for much faster equality checks than std::Path
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct FastPath {
-    path: String,
-}
-
-impl FastPath {
-    pub fn new(path: &PathBuf) -> Self {
-        Self {
-            path: path
-                .to_string_lossy()
-                .trim_end_matches(MAIN_SEPARATOR)
-                .to_string(),
-        }
-    }
-}
-
-impl Display for FastPath {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.path)
-    }
-}
-
+mod age;
 mod args;
+mod categories;
+mod content_type;
+mod dir_sizes;
+mod duplicates;
+mod errors;
+mod extensions;
+mod human_size;
 mod info;
+mod largest_files;
+mod owners;
+mod permissions;
+mod size_histogram;
+mod snapshot;
+mod snapshot_diff;
 mod timer;
+mod work_queue;
 
-use std::{
-    fmt::Display,
-    path::{PathBuf, MAIN_SEPARATOR},
-};
-
+pub use age::*;
 pub use args::*;
+pub use categories::*;
+pub use content_type::*;
+pub use dir_sizes::*;
+pub use duplicates::*;
+pub use errors::*;
+pub use extensions::*;
+pub use human_size::*;
 pub use info::*;
+pub use largest_files::*;
+pub use owners::*;
+pub use permissions::*;
+pub use size_histogram::*;
+pub use snapshot::*;
+pub use snapshot_diff::*;
 pub use timer::*;
+pub use work_queue::*;
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{format_size, DisplaySizes, SizeUnits, Totals};
+
+///Files owned by one user or group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnerTotals {
+    ///uid or gid
+    id: u32,
+    ///Name from the passwd or group database, `None` if the id is orphaned
+    name: Option<String>,
+    ///If no user or group with this id exists, e.g. the account was deleted
+    orphaned: bool,
+    #[serde(flatten)]
+    totals: Totals,
+}
+
+impl OwnerTotals {
+    ///uid or gid
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    ///Name from the passwd or group database, `None` if the id is orphaned
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    ///If no user or group with this id exists, e.g. the account was deleted
+    pub fn orphaned(&self) -> bool {
+        self.orphaned
+    }
+
+    pub fn totals(&self) -> Totals {
+        self.totals
+    }
+}
+
+impl std::fmt::Display for OwnerTotals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for OwnerTotals {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}({})", self.id)?,
+            None => write!(f, "{}(orphaned)", self.id)?,
+        }
+        write!(
+            f,
+            ": {} files, {}",
+            self.totals.files(),
+            format_size(self.totals.bytes(), units)
+        )
+    }
+}
+
+///Number of files and bytes owned by each user and group
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct OwnerInfo {
+    ///Gathered while walking and turned into `users` by `finish`
+    #[serde(skip)]
+    by_uid: HashMap<u32, Totals>,
+    ///Gathered while walking and turned into `groups` by `finish`
+    #[serde(skip)]
+    by_gid: HashMap<u32, Totals>,
+    ///Most bytes first
+    users: Vec<OwnerTotals>,
+    ///Most bytes first
+    groups: Vec<OwnerTotals>,
+}
+
+impl OwnerInfo {
+    pub fn add(&mut self, uid: u32, gid: u32, bytes: u64) {
+        self.by_uid.entry(uid).or_default().add(bytes);
+        self.by_gid.entry(gid).or_default().add(bytes);
+    }
+
+    ///Adds the files counted by another thread
+    pub fn merge(&mut self, other: OwnerInfo) {
+        for (uid, totals) in other.by_uid {
+            self.by_uid.entry(uid).or_default().merge(totals);
+        }
+        for (gid, totals) in other.by_gid {
+            self.by_gid.entry(gid).or_default().merge(totals);
+        }
+    }
+
+    ///Looks up the name of each user and group once all files are added
+    pub fn finish(&mut self) {
+        self.users = resolve(std::mem::take(&mut self.by_uid), user_name);
+        self.groups = resolve(std::mem::take(&mut self.by_gid), group_name);
+    }
+
+    ///Most bytes first
+    pub fn users(&self) -> &[OwnerTotals] {
+        &self.users
+    }
+
+    ///Most bytes first
+    pub fn groups(&self) -> &[OwnerTotals] {
+        &self.groups
+    }
+}
+
+impl std::fmt::Display for OwnerInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for OwnerInfo {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(f, "\nOwners:\n  Users:")?;
+        for user in &self.users {
+            write!(f, "\n    {}", user.with_units(units))?;
+        }
+        write!(f, "\n  Groups:")?;
+        for group in &self.groups {
+            write!(f, "\n    {}", group.with_units(units))?;
+        }
+        Ok(())
+    }
+}
+
+///Most bytes first, ties are broken by id so the order is the same on every run
+fn resolve(totals: HashMap<u32, Totals>, name: fn(u32) -> Option<String>) -> Vec<OwnerTotals> {
+    let mut out: Vec<_> = totals
+        .into_iter()
+        .map(|(id, totals)| {
+            let name = name(id);
+            OwnerTotals {
+                id,
+                orphaned: name.is_none(),
+                name,
+                totals,
+            }
+        })
+        .collect();
+    out.sort_by(|a, b| {
+        b.totals
+            .bytes()
+            .cmp(&a.totals.bytes())
+            .then(a.id.cmp(&b.id))
+    });
+    out
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    uzers::get_user_by_uid(uid).map(|user| user.name().to_string_lossy().to_string())
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> Option<String> {
+    uzers::get_group_by_gid(gid).map(|group| group.name().to_string_lossy().to_string())
+}
+
+//Files don't have a uid or gid on other platforms so nothing is ever added
+#[cfg(not(unix))]
+fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+fn group_name(_gid: u32) -> Option<String> {
+    None
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Serialize, Serializer};
+
+use super::serialize_path_lossy;
+
+const WORLD_WRITABLE: u32 = 0o002;
+const STICKY: u32 = 0o1000;
+const SETGID: u32 = 0o2000;
+const SETUID: u32 = 0o4000;
+
+///Why an entry was reported by --audit-permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionIssue {
+    ///Anyone can write to the file or directory
+    WorldWritable,
+    ///The file runs as its owner
+    Setuid,
+    ///The file runs as its group
+    Setgid,
+    ///Anyone can write to the directory but only the owner of a file should be able to delete it
+    MissingStickyBit,
+    ///The sticky bit does nothing on a file
+    StickyFile,
+    ///The file or directory is owned by a different user than the directory it is in
+    OwnerDiffersFromParent,
+}
+
+impl std::fmt::Display for PermissionIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            PermissionIssue::WorldWritable => "world-writable",
+            PermissionIssue::Setuid => "setuid",
+            PermissionIssue::Setgid => "setgid",
+            PermissionIssue::MissingStickyBit => "world-writable directory without the sticky bit",
+            PermissionIssue::StickyFile => "sticky bit on a file",
+            PermissionIssue::OwnerDiffersFromParent => {
+                "owned by a different user than its directory"
+            }
+        };
+        write!(f, "{str}")
+    }
+}
+
+///A file or directory reported by --audit-permissions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PermissionFinding {
+    issue: PermissionIssue,
+    #[serde(serialize_with = "serialize_path_lossy")]
+    path: PathBuf,
+    ///Permission bits including setuid, setgid and sticky, serialized in octal e.g. "4755"
+    #[serde(serialize_with = "serialize_mode")]
+    mode: u32,
+    ///uid of the owner
+    uid: u32,
+    ///uid of the owner of the directory the entry is in, only for OwnerDiffersFromParent
+    parent_uid: Option<u32>,
+}
+
+impl PermissionFinding {
+    pub fn issue(&self) -> PermissionIssue {
+        self.issue
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    ///Permission bits including setuid, setgid and sticky
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    ///uid of the owner
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    ///uid of the owner of the directory the entry is in, only for OwnerDiffersFromParent
+    pub fn parent_uid(&self) -> Option<u32> {
+        self.parent_uid
+    }
+}
+
+impl std::fmt::Display for PermissionFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:04o}", self.path.to_string_lossy(), self.mode)?;
+        match self.parent_uid {
+            Some(parent_uid) => write!(f, " uid {}, directory uid {parent_uid}", self.uid),
+            None => Ok(()),
+        }
+    }
+}
+
+///Entries with risky or unusual permissions found by --audit-permissions
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct PermissionAudit {
+    ///Sorted by issue and then path
+    findings: Vec<PermissionFinding>,
+}
+
+impl PermissionAudit {
+    ///Records every issue with an entry. `mode` is the full st_mode, `parent_uid` is the owner
+    ///of the directory the entry is in if it is known
+    pub fn check(
+        &mut self,
+        path: &Path,
+        mode: u32,
+        uid: u32,
+        is_dir: bool,
+        parent_uid: Option<u32>,
+    ) {
+        let mode = mode & 0o7777;
+        let mut add = |issue, parent_uid| {
+            self.findings.push(PermissionFinding {
+                issue,
+                path: path.to_path_buf(),
+                mode,
+                uid,
+                parent_uid,
+            })
+        };
+        if mode & WORLD_WRITABLE != 0 {
+            add(PermissionIssue::WorldWritable, None);
+        }
+        if let Some(parent_uid) = parent_uid.filter(|parent_uid| *parent_uid != uid) {
+            add(PermissionIssue::OwnerDiffersFromParent, Some(parent_uid));
+        }
+        if is_dir {
+            if mode & WORLD_WRITABLE != 0 && mode & STICKY == 0 {
+                add(PermissionIssue::MissingStickyBit, None);
+            }
+            //setgid on a directory only makes new entries inherit its group, so it is left out
+            return;
+        }
+        if mode & SETUID != 0 {
+            add(PermissionIssue::Setuid, None);
+        }
+        if mode & SETGID != 0 {
+            add(PermissionIssue::Setgid, None);
+        }
+        if mode & STICKY != 0 {
+            add(PermissionIssue::StickyFile, None);
+        }
+    }
+
+    ///Adds the findings of another thread
+    pub fn merge(&mut self, other: PermissionAudit) {
+        self.findings.extend(other.findings);
+    }
+
+    ///Sorts the findings once all entries are checked so the order is the same on every run
+    pub fn finish(&mut self) {
+        self.findings
+            .sort_by(|a, b| a.issue.cmp(&b.issue).then_with(|| a.path.cmp(&b.path)));
+    }
+
+    ///Sorted by issue and then path
+    pub fn findings(&self) -> &[PermissionFinding] {
+        &self.findings
+    }
+}
+
+impl std::fmt::Display for PermissionAudit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\nPermission audit: {} findings", self.findings.len())?;
+        let mut issue = None;
+        for finding in &self.findings {
+            if issue != Some(finding.issue) {
+                issue = Some(finding.issue);
+                write!(f, "\n  {}:", finding.issue)?;
+            }
+            write!(f, "\n    {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+fn serialize_mode<S: Serializer>(mode: &u32, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("{mode:04o}"))
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::{format_size, DisplaySizes, SizeUnits};
+
+///Upper limit and label of each size range except the last, which has no limit
+const SIZE_RANGES: [(u64, &str); 6] = [
+    (1, "0"),
+    (1024, "<1K"),
+    (4 * 1024, "<4K"),
+    (64 * 1024, "<64K"),
+    (1024 * 1024, "<1M"),
+    (100 * 1024 * 1024, "<100M"),
+];
+const LARGEST_RANGE: &str = ">=100M";
+
+///Files whose size is in one range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeBucket {
+    ///e.g. "<4K", sizes are in powers of 1024
+    label: &'static str,
+    files: u32,
+    bytes: u64,
+}
+
+impl SizeBucket {
+    ///e.g. "<4K", sizes are in powers of 1024
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn files(&self) -> u32 {
+        self.files
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+///How far the median can be from the real one, as a fraction of it
+const MEDIAN_ACCURACY: f64 = 0.01;
+
+///Count and bytes of files in log scale size ranges, along with the mean and median size
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SizeHistogram {
+    buckets: Vec<SizeBucket>,
+    ///Mean size in bytes, set by `finish`
+    mean: Option<f64>,
+    ///Median size in bytes within `MEDIAN_ACCURACY` of the real one, set by `finish`
+    median: Option<f64>,
+    ///Sizes of the files added, only kept until the median is found
+    #[serde(skip)]
+    sketch: SizeSketch,
+}
+
+impl Default for SizeHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SizeHistogram {
+    pub fn new() -> Self {
+        let labels = SIZE_RANGES.iter().map(|(_, label)| *label);
+        Self {
+            buckets: labels
+                .chain([LARGEST_RANGE])
+                .map(|label| SizeBucket {
+                    label,
+                    files: 0,
+                    bytes: 0,
+                })
+                .collect(),
+            mean: None,
+            median: None,
+            sketch: SizeSketch::default(),
+        }
+    }
+
+    pub fn buckets(&self) -> &[SizeBucket] {
+        &self.buckets
+    }
+
+    ///Mean size in bytes, `None` until `finish` is called or if there are no files
+    pub fn mean(&self) -> Option<f64> {
+        self.mean
+    }
+
+    ///Median size in bytes, `None` until `finish` is called or if there are no files.
+    ///
+    ///Estimated so every size doesn't need to be kept, it is within 1% of the real median
+    pub fn median(&self) -> Option<f64> {
+        self.median
+    }
+
+    pub fn add(&mut self, size: u64) {
+        let i = SIZE_RANGES.partition_point(|&(limit, _)| limit <= size);
+        self.buckets[i].files += 1;
+        self.buckets[i].bytes += size;
+        self.sketch.add(size);
+    }
+
+    ///Adds the files counted by another thread
+    pub fn merge(&mut self, other: SizeHistogram) {
+        for (bucket, other) in self.buckets.iter_mut().zip(&other.buckets) {
+            bucket.files += other.files;
+            bucket.bytes += other.bytes;
+        }
+        self.sketch.merge(other.sketch);
+    }
+
+    ///Calculates the mean and median once all files are added
+    pub fn finish(&mut self) {
+        let sketch = std::mem::take(&mut self.sketch);
+        let files: u64 = self.buckets.iter().map(|b| u64::from(b.files)).sum();
+        if files == 0 {
+            return;
+        }
+        let total: u64 = self.buckets.iter().map(|b| b.bytes).sum();
+        self.mean = Some(total as f64 / files as f64);
+        self.median = Some(if files.is_multiple_of(2) {
+            //With an even count the median is halfway between the two middle sizes
+            (sketch.nth(files / 2) + sketch.nth(files / 2 + 1)) / 2.0
+        } else {
+            sketch.nth(files / 2 + 1)
+        });
+    }
+}
+
+///Counts of sizes in log scale bins narrow enough that any size in a bin is within
+///`MEDIAN_ACCURACY` of the size the bin stands for, like DDSketch. Only bins that have sizes are
+///stored so it stays small no matter how many files are added
+#[derive(Debug, Clone, PartialEq, Default)]
+struct SizeSketch {
+    zeros: u64,
+    ///Bin `i` holds the sizes in (GAMMA^(i-1), GAMMA^i]
+    bins: BTreeMap<i32, u64>,
+}
+
+impl SizeSketch {
+    ///How much larger the sizes in each bin are than the ones in the bin before it
+    const GAMMA: f64 = (1.0 + MEDIAN_ACCURACY) / (1.0 - MEDIAN_ACCURACY);
+
+    fn add(&mut self, size: u64) {
+        match size {
+            0 => self.zeros += 1,
+            size => {
+                let bin = ((size as f64).ln() / Self::GAMMA.ln()).ceil() as i32;
+                *self.bins.entry(bin).or_default() += 1;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: SizeSketch) {
+        self.zeros += other.zeros;
+        for (bin, count) in other.bins {
+            *self.bins.entry(bin).or_default() += count;
+        }
+    }
+
+    ///Estimated size of the `n`th smallest size counting from 1
+    fn nth(&self, n: u64) -> f64 {
+        if n <= self.zeros {
+            return 0.0;
+        }
+        let mut seen = self.zeros;
+        for (bin, count) in &self.bins {
+            seen += count;
+            if seen >= n {
+                //The point in the bin that is the same fraction away from both of its ends
+                return 2.0 * Self::GAMMA.powi(*bin) / (Self::GAMMA + 1.0);
+            }
+        }
+        unreachable!("n is at most the number of sizes")
+    }
+}
+
+impl std::fmt::Display for SizeHistogram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for SizeHistogram {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        let size = |bytes: f64| match units {
+            Some(_) => format_size(bytes.round() as u64, units),
+            None => format!("{bytes:.1} bytes"),
+        };
+        if let (Some(mean), Some(median)) = (self.mean, self.median) {
+            write!(f, " mean {}, median {}", size(mean), size(median))?;
+        }
+        for bucket in &self.buckets {
+            write!(
+                f,
+                "\n    {}: {} files, {}",
+                bucket.label,
+                bucket.files,
+                format_size(bucket.bytes, units)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use super::{AnalyzedInfo, FileExtension};
+
+///Version of the snapshot format, snapshots with a different version can't be compared.
+///
+///Bumped whenever a field is renamed, removed or changes meaning
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+///Number of files and their total size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Totals {
+    files: u64,
+    bytes: u64,
+}
+
+impl Totals {
+    pub fn new(files: u64, bytes: u64) -> Self {
+        Self { files, bytes }
+    }
+
+    pub fn files(&self) -> u64 {
+        self.files
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    ///Adds one file of `bytes`
+    pub(crate) fn add(&mut self, bytes: u64) {
+        self.files += 1;
+        self.bytes += bytes;
+    }
+
+    pub(crate) fn merge(&mut self, other: Totals) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+    }
+}
+
+///Totals of the files found, gathered while walking for --save-snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SnapshotTotals {
+    ///The path that is analyzed, the other paths are made relative to it
+    root: PathBuf,
+    total: Totals,
+    ///Keyed the same as file_info so --normalize-extensions, --extension-alias and
+    ///--detect-type apply
+    extensions: BTreeMap<FileExtension, Totals>,
+    dirs: BTreeMap<String, Totals>,
+    ///Only with --snapshot-files
+    files: Option<BTreeMap<String, u64>>,
+}
+
+impl SnapshotTotals {
+    pub fn new(root: PathBuf, include_files: bool) -> Self {
+        Self {
+            root,
+            files: include_files.then(BTreeMap::new),
+            ..Default::default()
+        }
+    }
+
+    ///Adds the file at `path`, `ext` is the key it is grouped under in file_info
+    pub fn add(&mut self, path: &Path, ext: &str, bytes: u64) {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        self.total.add(bytes);
+        match self.extensions.get_mut(ext) {
+            Some(totals) => totals.add(bytes),
+            None => {
+                let mut totals = Totals::default();
+                totals.add(bytes);
+                self.extensions.insert(ext.to_string(), totals);
+            }
+        }
+        for dir in relative.ancestors().skip(1) {
+            self.dirs.entry(relative_key(dir)).or_default().add(bytes);
+        }
+        if let Some(files) = &mut self.files {
+            files.insert(relative_key(relative), bytes);
+        }
+    }
+
+    ///Adds the files found by another thread
+    pub fn merge(&mut self, other: SnapshotTotals) {
+        self.total.merge(other.total);
+        for (ext, totals) in other.extensions {
+            self.extensions.entry(ext).or_default().merge(totals);
+        }
+        for (dir, totals) in other.dirs {
+            self.dirs.entry(dir).or_default().merge(totals);
+        }
+        if let (Some(files), Some(other)) = (&mut self.files, other.files) {
+            files.extend(other);
+        }
+    }
+}
+
+///An analysis saved with --save-snapshot, two of them can be compared with `dira diff`.
+///
+///Paths are relative to the path that was analyzed and use `/` as the separator, so snapshots
+///of the same tree taken from different places can still be compared
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    snapshot_version: u32,
+    ///The path that was analyzed
+    path: String,
+    ///When the snapshot was taken, seconds since the Unix epoch
+    created: i64,
+    ///The analysis, the same as the JSON output without the schema version
+    info: serde_json::Value,
+    ///All the files found
+    total: Totals,
+    ///Totals of each extension, "" for files without one
+    extensions: BTreeMap<FileExtension, Totals>,
+    ///Totals of each directory that has files below it, "." for the path that was analyzed
+    dirs: BTreeMap<String, Totals>,
+    ///Size of every file, only with --snapshot-files
+    files: Option<BTreeMap<String, u64>>,
+}
+
+impl Snapshot {
+    ///Builds a snapshot of `info`, which has to have been analyzed with --save-snapshot so it
+    ///holds the totals
+    pub fn new(root: &Path, info: &AnalyzedInfo) -> anyhow::Result<Self> {
+        let SnapshotTotals {
+            total,
+            extensions,
+            dirs,
+            files,
+            ..
+        } = info.snapshot_totals().cloned().unwrap_or_default();
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        Ok(Self {
+            snapshot_version: SNAPSHOT_VERSION,
+            path: root.to_string_lossy().to_string(),
+            created,
+            info: serde_json::to_value(info)?,
+            total,
+            extensions,
+            dirs,
+            files,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("could not save snapshot to {}", path.to_string_lossy()))
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("could not read snapshot {}", path.to_string_lossy()))?;
+        let snapshot: Snapshot = serde_json::from_str(&contents)
+            .with_context(|| format!("{} is not a dira snapshot", path.to_string_lossy()))?;
+        if snapshot.snapshot_version != SNAPSHOT_VERSION {
+            bail!(
+                "{} is a version {} snapshot, only version {SNAPSHOT_VERSION} is supported",
+                path.to_string_lossy(),
+                snapshot.snapshot_version
+            );
+        }
+        Ok(snapshot)
+    }
+
+    ///The path that was analyzed
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    ///When the snapshot was taken, seconds since the Unix epoch
+    pub fn created(&self) -> i64 {
+        self.created
+    }
+
+    ///The analysis, the same as the JSON output without the schema version
+    pub fn info(&self) -> &serde_json::Value {
+        &self.info
+    }
+
+    ///All the files found
+    pub fn total(&self) -> Totals {
+        self.total
+    }
+
+    ///Totals of each extension, "" for files without one
+    pub fn extensions(&self) -> &BTreeMap<FileExtension, Totals> {
+        &self.extensions
+    }
+
+    ///Totals of each directory that has files below it, "." for the path that was analyzed
+    pub fn dirs(&self) -> &BTreeMap<String, Totals> {
+        &self.dirs
+    }
+
+    ///Size of every file, only with --snapshot-files
+    pub fn files(&self) -> Option<&BTreeMap<String, u64>> {
+        self.files.as_ref()
+    }
+}
+
+///`path` with `/` separators, "." if it is empty
+fn relative_key(path: &Path) -> String {
+    let parts: Vec<_> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    match parts.is_empty() {
+        true => ".".to_string(),
+        false => parts.join("/"),
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use super::{
+    format_size, format_size_change, format_timestamp, DisplaySizes, SizeUnits, Snapshot, Totals,
+};
+
+///How the totals of an extension or directory changed between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TotalsChange {
+    ///The extension or directory
+    name: String,
+    old: Totals,
+    new: Totals,
+    files_change: i64,
+    bytes_change: i64,
+}
+
+impl TotalsChange {
+    pub fn new(name: String, old: Totals, new: Totals) -> Self {
+        Self {
+            name,
+            old,
+            new,
+            files_change: new.files() as i64 - old.files() as i64,
+            bytes_change: new.bytes() as i64 - old.bytes() as i64,
+        }
+    }
+
+    ///The extension or directory
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn old(&self) -> Totals {
+        self.old
+    }
+
+    pub fn new_totals(&self) -> Totals {
+        self.new
+    }
+
+    pub fn files_change(&self) -> i64 {
+        self.files_change
+    }
+
+    pub fn bytes_change(&self) -> i64 {
+        self.bytes_change
+    }
+}
+
+impl std::fmt::Display for TotalsChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for TotalsChange {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{} -> {} files({:+}), {} -> {}({})",
+            self.old.files(),
+            self.new.files(),
+            self.files_change,
+            format_size(self.old.bytes(), units),
+            format_size(self.new.bytes(), units),
+            format_size_change(self.bytes_change, units)
+        )
+    }
+}
+
+///A file that was added, removed or grew between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChange {
+    path: String,
+    ///`None` if the file was added
+    old_size: Option<u64>,
+    ///`None` if the file was removed
+    new_size: Option<u64>,
+}
+
+impl FileChange {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    ///`None` if the file was added
+    pub fn old_size(&self) -> Option<u64> {
+        self.old_size
+    }
+
+    ///`None` if the file was removed
+    pub fn new_size(&self) -> Option<u64> {
+        self.new_size
+    }
+
+    pub fn size_change(&self) -> i64 {
+        self.new_size.unwrap_or_default() as i64 - self.old_size.unwrap_or_default() as i64
+    }
+}
+
+///Files that were added, removed or grew between two snapshots, largest change first
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct FileChanges {
+    added: Vec<FileChange>,
+    removed: Vec<FileChange>,
+    grown: Vec<FileChange>,
+}
+
+impl FileChanges {
+    fn new(old: &BTreeMap<String, u64>, new: &BTreeMap<String, u64>) -> Self {
+        let mut out = FileChanges::default();
+        for path in old.keys().chain(new.keys()).collect::<BTreeSet<_>>() {
+            let change = FileChange {
+                path: path.clone(),
+                old_size: old.get(path).copied(),
+                new_size: new.get(path).copied(),
+            };
+            match (change.old_size, change.new_size) {
+                (None, Some(_)) => out.added.push(change),
+                (Some(_), None) => out.removed.push(change),
+                (Some(old), Some(new)) if new > old => out.grown.push(change),
+                _ => {}
+            }
+        }
+        //Stable sorts so ties stay ordered by path
+        out.added.sort_by_key(|c| std::cmp::Reverse(c.new_size));
+        out.removed.sort_by_key(|c| std::cmp::Reverse(c.old_size));
+        out.grown
+            .sort_by_key(|c| std::cmp::Reverse(c.size_change()));
+        out
+    }
+
+    pub fn added(&self) -> &[FileChange] {
+        &self.added
+    }
+
+    pub fn removed(&self) -> &[FileChange] {
+        &self.removed
+    }
+
+    pub fn grown(&self) -> &[FileChange] {
+        &self.grown
+    }
+}
+
+///What changed between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SnapshotDiff {
+    old_path: String,
+    new_path: String,
+    ///When the snapshots were taken, seconds since the Unix epoch
+    old_created: i64,
+    new_created: i64,
+    ///All the files found
+    total: TotalsChange,
+    ///Extensions whose totals changed, most growth first
+    extensions: Vec<TotalsChange>,
+    ///Directories whose totals changed, most growth first
+    dirs: Vec<TotalsChange>,
+    ///Only if both snapshots were saved with --snapshot-files
+    files: Option<FileChanges>,
+}
+
+impl SnapshotDiff {
+    pub fn new(old: &Snapshot, new: &Snapshot) -> Self {
+        let files = match (old.files(), new.files()) {
+            (Some(old), Some(new)) => Some(FileChanges::new(old, new)),
+            _ => None,
+        };
+        Self {
+            old_path: old.path().to_string(),
+            new_path: new.path().to_string(),
+            old_created: old.created(),
+            new_created: new.created(),
+            total: TotalsChange::new("All files".to_string(), old.total(), new.total()),
+            extensions: changed_totals(old.extensions(), new.extensions()),
+            dirs: changed_totals(old.dirs(), new.dirs()),
+            files,
+        }
+    }
+
+    pub fn old_path(&self) -> &str {
+        &self.old_path
+    }
+
+    pub fn new_path(&self) -> &str {
+        &self.new_path
+    }
+
+    ///When the earlier snapshot was taken, seconds since the Unix epoch
+    pub fn old_created(&self) -> i64 {
+        self.old_created
+    }
+
+    ///When the later snapshot was taken, seconds since the Unix epoch
+    pub fn new_created(&self) -> i64 {
+        self.new_created
+    }
+
+    ///All the files found
+    pub fn total(&self) -> &TotalsChange {
+        &self.total
+    }
+
+    ///Extensions whose totals changed, most growth first
+    pub fn extensions(&self) -> &[TotalsChange] {
+        &self.extensions
+    }
+
+    ///Directories whose totals changed, most growth first
+    pub fn dirs(&self) -> &[TotalsChange] {
+        &self.dirs
+    }
+
+    ///Only if both snapshots were saved with --snapshot-files
+    pub fn files(&self) -> Option<&FileChanges> {
+        self.files.as_ref()
+    }
+}
+
+impl std::fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_sizes(f, None)
+    }
+}
+
+impl DisplaySizes for SnapshotDiff {
+    fn fmt_sizes(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        units: Option<SizeUnits>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "Old snapshot: {} taken {}\nNew snapshot: {} taken {}\nAll files: {}",
+            self.old_path,
+            format_timestamp(self.old_created),
+            self.new_path,
+            format_timestamp(self.new_created),
+            self.total.with_units(units)
+        )?;
+        write!(f, "\nExtension changes: {}", self.extensions.len())?;
+        for change in &self.extensions {
+            let name = match change.name.as_str() {
+                "" => "(no extension)",
+                name => name,
+            };
+            write!(f, "\n  {name}: {}", change.with_units(units))?;
+        }
+        write!(f, "\nDirectory changes: {}", self.dirs.len())?;
+        for change in &self.dirs {
+            write!(f, "\n  {}: {}", change.name, change.with_units(units))?;
+        }
+        let Some(files) = &self.files else {
+            return write!(
+                f,
+                "\nFile changes are only shown if both snapshots were saved with --snapshot-files"
+            );
+        };
+        write!(f, "\nAdded files: {}", files.added.len())?;
+        for change in &files.added {
+            let size = change.new_size.unwrap_or_default();
+            write!(f, "\n  {}: {}", change.path, format_size(size, units))?;
+        }
+        write!(f, "\nRemoved files: {}", files.removed.len())?;
+        for change in &files.removed {
+            let size = change.old_size.unwrap_or_default();
+            write!(f, "\n  {}: {}", change.path, format_size(size, units))?;
+        }
+        write!(f, "\nGrown files: {}", files.grown.len())?;
+        for change in &files.grown {
+            write!(
+                f,
+                "\n  {}: {} -> {}({})",
+                change.path,
+                format_size(change.old_size.unwrap_or_default(), units),
+                format_size(change.new_size.unwrap_or_default(), units),
+                format_size_change(change.size_change(), units)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+///Totals that differ between `old` and `new`, most growth first and then by name
+fn changed_totals(
+    old: &BTreeMap<String, Totals>,
+    new: &BTreeMap<String, Totals>,
+) -> Vec<TotalsChange> {
+    let names: BTreeSet<_> = old.keys().chain(new.keys()).collect();
+    let mut out: Vec<_> = names
+        .into_iter()
+        .map(|name| {
+            TotalsChange::new(
+                name.clone(),
+                old.get(name).copied().unwrap_or_default(),
+                new.get(name).copied().unwrap_or_default(),
+            )
+        })
+        .filter(|change| change.old != change.new)
+        .collect();
+    //Stable sort so ties stay ordered by name
+    out.sort_by_key(|change| std::cmp::Reverse(change.bytes_change));
+    out
+}
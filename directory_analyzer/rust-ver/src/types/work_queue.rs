@@ -0,0 +1,138 @@
+use std::{
+    ops::Deref,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+///Stack of work shared between the threads walking the directory tree.
+///
+///Keeps track of how many items are currently being worked on so that `pop` only returns `None`
+///once there is no work left and no thread can add more.
+#[derive(Debug)]
+pub struct WorkQueue<T> {
+    state: Mutex<WorkQueueState<T>>,
+    cond: Condvar,
+}
+
+#[derive(Debug)]
+struct WorkQueueState<T> {
+    items: Vec<T>,
+    ///Number of items that have been popped but not marked as done
+    in_progress: usize,
+    aborted: bool,
+}
+
+impl<T> WorkQueue<T> {
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            state: Mutex::new(WorkQueueState {
+                items,
+                in_progress: 0,
+                aborted: false,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    pub fn push(&self, item: T) {
+        let mut state = self.state.lock().unwrap();
+        state.items.push(item);
+        self.cond.notify_one();
+    }
+
+    ///Blocks until there is an item to work on.
+    ///
+    ///Returns `None` when all work is finished or the queue was aborted.
+    ///The item is marked as done when the returned `WorkItem` is dropped.
+    pub fn pop(&self) -> Option<WorkItem<'_, T>> {
+        let mut state = self.lock();
+        loop {
+            if state.aborted {
+                return None;
+            }
+            if let Some(item) = state.items.pop() {
+                state.in_progress += 1;
+                return Some(WorkItem { item, queue: self });
+            }
+            if state.in_progress == 0 {
+                return None;
+            }
+            state = self.cond.wait(state).unwrap();
+        }
+    }
+
+    ///Marks an item returned by `pop` as finished
+    fn done(&self) {
+        let mut state = self.lock();
+        state.in_progress -= 1;
+        if state.in_progress == 0 && state.items.is_empty() {
+            self.cond.notify_all();
+        }
+    }
+
+    ///Stops all threads waiting on the queue, used when one of them hits an error
+    pub fn abort(&self) {
+        let mut state = self.lock();
+        state.aborted = true;
+        self.cond.notify_all();
+    }
+
+    ///The lock is only held while the queue itself is changed, so a thread that panicked while
+    ///holding it left the state whole
+    fn lock(&self) -> MutexGuard<'_, WorkQueueState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+///An item returned by `WorkQueue::pop`, marked as done when dropped.
+///
+///Dropping it during a panic aborts the queue so the other threads stop instead of waiting on
+///work that will never be done
+#[derive(Debug)]
+pub struct WorkItem<'a, T> {
+    item: T,
+    queue: &'a WorkQueue<T>,
+}
+
+impl<T> Deref for WorkItem<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+impl<T> Drop for WorkItem<'_, T> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.queue.abort();
+        }
+        self.queue.done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panicking_worker_does_not_hang_others() {
+        let queue = WorkQueue::new(vec![1, 2, 3]);
+        let results: Vec<_> = thread::scope(|s| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    s.spawn(|| {
+                        while let Some(item) = queue.pop() {
+                            if *item == 3 {
+                                panic!("worker failed on item 3");
+                            }
+                            thread::sleep(std::time::Duration::from_millis(10));
+                        }
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join()).collect()
+        });
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+    }
+}